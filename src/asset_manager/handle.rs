@@ -0,0 +1,93 @@
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::sync::{Arc, Weak};
+
+/// Typed reference to an asset of the `AssetManager`.
+/// The asset stays loaded as long as a handle to it exists. A handle to an
+/// asset which was removed finds nothing, even once its slot is reused.
+pub struct Handle<T> {
+    pub(super) index: usize,
+    pub(super) generation: u32,
+    // Shared with the storage slot, which counts the handles through it.
+    refs: Arc<()>,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Handle which doesn't keep its asset loaded.
+pub struct WeakHandle<T> {
+    index: usize,
+    generation: u32,
+    refs: Weak<()>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub(super) fn new(index: usize, generation: u32, refs: Arc<()>) -> Self {
+        Self {
+            index,
+            generation,
+            refs,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        WeakHandle {
+            index: self.index,
+            generation: self.generation,
+            refs: Arc::downgrade(&self.refs),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> WeakHandle<T> {
+    /// A strong handle, if the asset is still loaded.
+    pub fn upgrade(&self) -> Option<Handle<T>> {
+        let refs = self.refs.upgrade()?;
+        Some(Handle::new(self.index, self.generation, refs))
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.index, self.generation, Arc::clone(&self.refs))
+    }
+}
+
+impl<T> Clone for WeakHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            generation: self.generation,
+            refs: Weak::clone(&self.refs),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl<T> Debug for WeakHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WeakHandle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
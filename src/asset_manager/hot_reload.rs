@@ -0,0 +1,78 @@
+use crate::{
+    constants::{SHADER_PATH, TEXTURE_PATH},
+    vfs::Vfs,
+};
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// Asset file changed on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Name of the texture file.
+    Texture(String),
+    /// Name of the source, without its extension.
+    Shader(String),
+}
+
+/// Watch the texture and shader directories, the ones of the file system
+/// mounted from the disk.
+pub struct HotReload {
+    receiver: Receiver<DebouncedEvent>,
+    #[allow(unused)]
+    watcher: RecommendedWatcher,
+}
+
+impl HotReload {
+    pub fn new(delay: u64, vfs: &Vfs) -> notify::Result<Self> {
+        let (sender, receiver) = channel();
+        let mut watcher: RecommendedWatcher =
+            Watcher::new(sender, Duration::from_secs(delay))?;
+
+        for dir in &[TEXTURE_PATH, SHADER_PATH] {
+            for path in vfs.directories(dir) {
+                watcher.watch(path, RecursiveMode::NonRecursive)?;
+            }
+        }
+
+        Ok(Self { receiver, watcher })
+    }
+
+    /// Files changed since the last call, each one once.
+    pub fn changes(&self) -> Vec<Change> {
+        let mut changes = vec![];
+
+        for event in self.receiver.try_iter() {
+            let change = match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => Self::change(&path),
+                _ => None,
+            };
+
+            match change {
+                Some(change) if !changes.contains(&change) => {
+                    changes.push(change)
+                }
+                _ => {}
+            }
+        }
+
+        changes
+    }
+
+    fn change(path: &Path) -> Option<Change> {
+        let dir = path.parent()?;
+
+        if dir.ends_with(TEXTURE_PATH) {
+            let file = path.file_name()?.to_str()?;
+            Some(Change::Texture(String::from(file)))
+        } else if dir.ends_with(SHADER_PATH) {
+            let name = path.file_stem()?.to_str()?;
+            Some(Change::Shader(String::from(name)))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::Ressource;
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// What a loader knows about the file it reads.
+pub struct LoadContext<'a> {
+    /// Name of the asset, relative to the directory of its type.
+    pub path: &'a str,
+}
+
+impl<'a> LoadContext<'a> {
+    /// Extension of the file, in lower case.
+    pub fn extension(&self) -> Option<String> {
+        extension(self.path)
+    }
+}
+
+/// Builds an asset from the content of a file.
+/// Loaders run on the workers of the asset manager, the asset is sent to
+/// the GPU afterwards on the main thread, see `Ressource::gl_load`.
+pub trait AssetLoader: Send + Sync + 'static {
+    type Asset: Ressource;
+
+    /// Extensions of the files it reads, without the dot.
+    fn extensions(&self) -> &[&str];
+    fn load(
+        &self,
+        bytes: &[u8],
+        ctx: &LoadContext,
+    ) -> Result<Self::Asset, String>;
+}
+
+/// Loaders of the asset manager, by file extension.
+/// Several asset types can share an extension, the loader is picked by the
+/// type of asset asked for.
+#[derive(Default)]
+pub struct LoaderRegistry {
+    // Each loader is stored as an `Arc<dyn AssetLoader<Asset = T>>`.
+    loaders: HashMap<String, Vec<Box<dyn Any + Send + Sync>>>,
+}
+
+impl LoaderRegistry {
+    pub fn register<L: AssetLoader>(&mut self, loader: L) {
+        let loader: Arc<dyn AssetLoader<Asset = L::Asset>> = Arc::new(loader);
+
+        for ext in loader.extensions() {
+            let loaders = self.loaders.entry(ext.to_lowercase()).or_default();
+
+            // A new loader replaces the one of the same asset type.
+            loaders.retain(|other| {
+                !other.is::<Arc<dyn AssetLoader<Asset = L::Asset>>>()
+            });
+            loaders.push(Box::new(Arc::clone(&loader)));
+        }
+    }
+
+    /// Loader of the assets of type `T` for the extension of the path.
+    pub fn get<T: Ressource>(
+        &self,
+        path: &str,
+    ) -> Option<Arc<dyn AssetLoader<Asset = T>>> {
+        self.loaders
+            .get(&extension(path)?)?
+            .iter()
+            .find_map(|loader| loader.downcast_ref())
+            .cloned()
+    }
+}
+
+fn extension(path: &str) -> Option<String> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    Some(ext.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Text(String);
+    impl Ressource for Text {
+        const DIRECTORY: &'static str = "";
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Size(usize);
+    impl Ressource for Size {
+        const DIRECTORY: &'static str = "";
+    }
+
+    struct TextLoader;
+    impl AssetLoader for TextLoader {
+        type Asset = Text;
+
+        fn extensions(&self) -> &[&str] {
+            &["txt", "ron"]
+        }
+
+        fn load(&self, bytes: &[u8], _: &LoadContext) -> Result<Text, String> {
+            String::from_utf8(bytes.to_vec())
+                .map(Text)
+                .map_err(|err| err.to_string())
+        }
+    }
+
+    struct SizeLoader;
+    impl AssetLoader for SizeLoader {
+        type Asset = Size;
+
+        fn extensions(&self) -> &[&str] {
+            &["ron"]
+        }
+
+        fn load(&self, bytes: &[u8], _: &LoadContext) -> Result<Size, String> {
+            Ok(Size(bytes.len()))
+        }
+    }
+
+    #[test]
+    fn should_find_loader_by_extension() {
+        let mut registry = LoaderRegistry::default();
+        registry.register(TextLoader);
+        let ctx = LoadContext { path: "a.TXT" };
+
+        let loader = registry.get::<Text>("a.TXT").unwrap();
+        assert_eq!(loader.load(b"abc", &ctx), Ok(Text(String::from("abc"))));
+        assert_eq!(ctx.extension(), Some(String::from("txt")));
+
+        assert!(registry.get::<Text>("a.png").is_none());
+        assert!(registry.get::<Text>("a").is_none());
+        assert!(registry.get::<Size>("a.txt").is_none());
+    }
+
+    #[test]
+    fn should_pick_loader_by_asset_type() {
+        let mut registry = LoaderRegistry::default();
+        registry.register(TextLoader);
+        registry.register(SizeLoader);
+        let ctx = LoadContext { path: "a.ron" };
+
+        let text = registry.get::<Text>("a.ron").unwrap();
+        let size = registry.get::<Size>("a.ron").unwrap();
+        assert_eq!(text.load(b"ab", &ctx), Ok(Text(String::from("ab"))));
+        assert_eq!(size.load(b"ab", &ctx), Ok(Size(2)));
+    }
+}
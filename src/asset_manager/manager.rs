@@ -0,0 +1,416 @@
+use super::{
+    Apply, AssetLoader, AssetStorage, Change, Handle, HotReload, Job,
+    LoadContext, LoadQueue, LoadState, LoaderRegistry, Texture, TextureLoader,
+    WeakHandle,
+};
+use crate::{
+    constants::SHADER_PATH, ecs::Events, events::AssetEvent, shader::Shader,
+    vfs::Vfs,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::{marker::Send, mem};
+
+/// Types of assets the manager stores, each one in its own storage.
+pub trait Ressource: Sized + Send + 'static {
+    /// Directory of the files of this type, names of the assets are
+    /// relative to it.
+    const DIRECTORY: &'static str;
+
+    /// Send the asset to the GPU, on the main thread once it is loaded.
+    fn gl_load(&mut self) {}
+    /// Take the place of the asset, once its file changed on disk.
+    fn reload(&mut self, new: Self) {
+        mem::replace(self, new).release();
+        self.gl_load();
+    }
+    /// Free what the asset holds on the GPU, once it is unloaded.
+    fn release(self) {}
+}
+
+/// Storage of any asset type, so every one of them can be released.
+trait AnyStorage {
+    fn release_unused(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Ressource> AnyStorage for AssetStorage<T> {
+    fn release_unused(&mut self) {
+        self.remove_unused()
+            .into_iter()
+            .for_each(Ressource::release);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Assets of every type, read through the file system by the loader
+/// registered for their extension. Textures are loaded out of the box.
+pub struct AssetManager {
+    vfs: Arc<Vfs>,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    loaders: LoaderRegistry,
+    queue: LoadQueue,
+    hot_reload: Option<HotReload>,
+}
+
+unsafe impl Send for AssetManager {}
+unsafe impl Sync for AssetManager {}
+
+impl AssetManager {
+    pub fn new(vfs: Vfs) -> Self {
+        let mut manager = Self {
+            vfs: Arc::new(vfs),
+            storages: HashMap::new(),
+            loaders: LoaderRegistry::default(),
+            queue: LoadQueue::default(),
+            hot_reload: None,
+        };
+        manager.add_loader(TextureLoader);
+        manager
+    }
+
+    /// Files the assets are read from, scenes too.
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// The same files, for threads reading them.
+    pub fn shared_vfs(&self) -> Arc<Vfs> {
+        Arc::clone(&self.vfs)
+    }
+
+    /// Read the files with the extensions of the loader through it, a
+    /// loader of the same asset type is replaced.
+    pub fn add_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.loaders.register(loader);
+    }
+
+    /// Queue an asset of the directory of its type, its handle can be used
+    /// right away. It is sent to the GPU once loaded, see `update`.
+    pub fn load<T: Ressource>(&mut self, path: &str) -> Handle<T> {
+        if let Some(handle) = self.handle(path) {
+            return handle;
+        }
+
+        let (handle, _) = self.storage_mut::<T>().reserve(path);
+        let loader = match self.loaders.get::<T>(path) {
+            Some(loader) => loader,
+            None => {
+                let err = String::from("no loader for this extension");
+                eprintln!("Could not load {}: {}", path, err);
+                self.storage_mut().set(&handle, Err(err));
+                return handle;
+            }
+        };
+
+        let (started, done) = (handle.downgrade(), handle.downgrade());
+        let path = String::from(path);
+        let vfs = Arc::clone(&self.vfs);
+
+        self.queue.push(Job {
+            started: Box::new(move |manager| {
+                if let Some(handle) = started.upgrade() {
+                    manager
+                        .storage_mut::<T>()
+                        .set_state(&handle, LoadState::Loading);
+                }
+                None
+            }),
+            run: Box::new(move || {
+                let asset = AssetManager::read(&vfs, &*loader, &path);
+                Box::new(move |manager| manager.finish(&done, path, asset))
+            }),
+        });
+
+        handle
+    }
+
+    /// Read a file and build an asset from it, it doesn't need the GL
+    /// context so it can run on any thread.
+    fn read<T: Ressource>(
+        vfs: &Vfs,
+        loader: &dyn AssetLoader<Asset = T>,
+        path: &str,
+    ) -> Result<T, String> {
+        println!("Loading asset: {}", path);
+
+        let bytes = vfs
+            .read(&[T::DIRECTORY, path].join(""))
+            .map_err(|err| err.to_string())?;
+        loader.load(&bytes, &LoadContext { path })
+    }
+
+    /// Put an asset loaded by a worker in its slot, and send it to the GPU.
+    fn finish<T: Ressource>(
+        &mut self,
+        handle: &WeakHandle<T>,
+        name: String,
+        asset: Result<T, String>,
+    ) -> Option<AssetEvent> {
+        // Every handle was dropped while it was loading.
+        let handle = match handle.upgrade() {
+            Some(handle) => handle,
+            None => {
+                asset.map(Ressource::release).ok();
+                return None;
+            }
+        };
+
+        let storage = self.storage_mut();
+        match asset {
+            Ok(asset) => {
+                if let Some(old) = storage.set(&handle, Ok(asset)) {
+                    old.release();
+                }
+                if let Some(asset) = storage.get_mut(&handle) {
+                    asset.gl_load();
+                }
+                Some(AssetEvent::Loaded(name))
+            }
+            Err(err) => {
+                eprintln!("Could not load {}: {}", name, err);
+                storage.set(&handle, Err(err.clone()));
+                Some(AssetEvent::Failed(name, err))
+            }
+        }
+    }
+
+    /// Reload the textures and shaders when they change on disk, see
+    /// `update`.
+    pub fn watch(&mut self, delay: u64) -> notify::Result<()> {
+        self.hot_reload = Some(HotReload::new(delay, &self.vfs)?);
+        Ok(())
+    }
+
+    /// Reload the assets changed on disk, and apply what the workers are
+    /// done with, on the main thread.
+    pub fn update(&mut self, events: &mut Events<AssetEvent>) {
+        let changes = self
+            .hot_reload
+            .as_ref()
+            .map(HotReload::changes)
+            .unwrap_or_default();
+
+        for change in changes {
+            match change {
+                Change::Texture(name) => self.reload::<Texture>(&name),
+                Change::Shader(name) => {
+                    events.extend(self.reload_shader(&name))
+                }
+            }
+        }
+
+        let done: Vec<Apply> = self.queue.done().collect();
+        events.extend(done.into_iter().filter_map(|apply| apply(self)));
+    }
+
+    /// Block until the assets are loaded, or failed to, for the ones needed
+    /// right away. Their events aren't published.
+    pub fn wait<T: Ressource>(&mut self, handles: &[Handle<T>]) {
+        let pending = |manager: &Self| {
+            handles.iter().any(|handle| {
+                matches!(
+                    manager.load_state(handle),
+                    Some(LoadState::Queued) | Some(LoadState::Loading)
+                )
+            })
+        };
+
+        while pending(self) {
+            match self.queue.wait() {
+                Some(apply) => {
+                    apply(self);
+                }
+                None => return,
+            }
+        }
+    }
+
+    pub fn load_state<T: Ressource>(
+        &self,
+        handle: &Handle<T>,
+    ) -> Option<LoadState> {
+        self.storage()?.state(handle).cloned()
+    }
+
+    pub fn add_shader(
+        &mut self,
+        name: &str,
+        vert: &str,
+        frag: &str,
+    ) -> Handle<Shader> {
+        if let Some(handle) = self.handle(name) {
+            return handle;
+        }
+
+        let shader = Shader::new()
+            .with_vert(&self.vfs, vert)
+            .with_frag(&self.vfs, frag);
+        shader.bind_uniform_blocks();
+        self.insert(name, shader)
+    }
+
+    /// Store an asset built beforehand.
+    /// An asset already stored under this name is kept.
+    pub fn insert<T: Ressource>(&mut self, name: &str, asset: T) -> Handle<T> {
+        match self.handle(name) {
+            Some(handle) => {
+                asset.release();
+                handle
+            }
+            None => self.storage_mut().insert(name, asset).0,
+        }
+    }
+
+    /// A new handle to the asset stored under this name.
+    pub fn handle<T: Ressource>(&self, name: &str) -> Option<Handle<T>> {
+        self.storage()?.handle(name)
+    }
+
+    pub fn contains<T: Ressource>(&self, name: &str) -> bool {
+        match self.storage::<T>() {
+            Some(storage) => storage.contains(name),
+            None => false,
+        }
+    }
+
+    pub fn get<T: Ressource>(&self, handle: &Handle<T>) -> Option<&T> {
+        self.storage()?.get(handle)
+    }
+
+    pub fn get_mut<T: Ressource>(
+        &mut self,
+        handle: &Handle<T>,
+    ) -> Option<&mut T> {
+        self.storage_mut().get_mut(handle)
+    }
+
+    /// Unload an asset, even if handles to it exist.
+    #[allow(unused)]
+    pub fn remove<T: Ressource>(&mut self, name: &str) {
+        if let Some(asset) = self.storage_mut::<T>().remove(name) {
+            asset.release();
+        }
+    }
+
+    /// Unload the assets no handle refers to anymore.
+    pub fn release_unused(&mut self) {
+        self.storages
+            .values_mut()
+            .for_each(|storage| storage.release_unused());
+    }
+
+    /// Read a stored asset from the disk again in the background, it then
+    /// takes the place of the old one, see `Ressource::reload`.
+    /// The old one is kept if it can't be read.
+    pub fn reload<T: Ressource>(&mut self, name: &str) {
+        let loader = match self.loaders.get::<T>(name) {
+            Some(loader) if self.contains::<T>(name) => loader,
+            _ => return,
+        };
+
+        let name = String::from(name);
+        let vfs = Arc::clone(&self.vfs);
+        self.queue.push(Job {
+            started: Box::new(|_| None),
+            run: Box::new(move || {
+                let asset = AssetManager::read(&vfs, &*loader, &name);
+                Box::new(move |manager| manager.replace(name, asset))
+            }),
+        });
+    }
+
+    fn replace<T: Ressource>(
+        &mut self,
+        name: String,
+        asset: Result<T, String>,
+    ) -> Option<AssetEvent> {
+        let handle = self.handle::<T>(&name)?;
+
+        // It failed to load before, or isn't loaded yet.
+        let old = match self.get_mut(&handle) {
+            Some(old) => old,
+            None => return self.finish(&handle.downgrade(), name, asset),
+        };
+
+        match asset {
+            Ok(asset) => {
+                old.reload(asset);
+                Some(AssetEvent::Reloaded(name))
+            }
+            Err(err) => {
+                eprintln!("Could not reload {}: {}", name, err);
+                Some(AssetEvent::Failed(name, err))
+            }
+        }
+    }
+
+    /// Build the programs using a changed source again.
+    /// The old program is kept if the new one doesn't compile.
+    fn reload_shader(&mut self, source: &str) -> Vec<AssetEvent> {
+        let programs: Vec<(String, String, String)> = self
+            .storage::<Shader>()
+            .into_iter()
+            .flat_map(AssetStorage::iter)
+            .filter(|(_, shader)| shader.uses(source))
+            .map(|(name, shader)| {
+                let (vert, frag) = shader.sources();
+                (String::from(name), String::from(vert), String::from(frag))
+            })
+            .collect();
+
+        let mut events = vec![];
+        for (name, vert, frag) in programs {
+            match Shader::compile(&self.vfs, &vert, &frag) {
+                Ok(shader) => {
+                    shader.bind_uniform_blocks();
+                    if let Some(old) =
+                        self.storage_mut::<Shader>().get_mut_by_name(&name)
+                    {
+                        old.reload(shader);
+                    }
+                    events.push(AssetEvent::Reloaded(name));
+                }
+                Err(err) => {
+                    eprintln!("Could not reload {}: {}", name, err);
+                    events.push(AssetEvent::Failed(name, err));
+                }
+            }
+        }
+
+        events
+    }
+
+    fn storage<T: Ressource>(&self) -> Option<&AssetStorage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref()
+    }
+
+    fn storage_mut<T: Ressource>(&mut self) -> &mut AssetStorage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(AssetStorage::<T>::default()))
+            .as_any_mut()
+            .downcast_mut()
+            .expect("Storage of another asset type.")
+    }
+}
+
+impl Ressource for Shader {
+    const DIRECTORY: &'static str = SHADER_PATH;
+
+    fn release(self) {
+        self.delete_program();
+    }
+}
@@ -0,0 +1,15 @@
+mod handle;
+mod hot_reload;
+mod loader;
+mod manager;
+mod queue;
+mod storage;
+mod texture;
+
+pub use handle::*;
+pub use hot_reload::*;
+pub use loader::*;
+pub use manager::*;
+pub use queue::*;
+pub use storage::*;
+pub use texture::*;
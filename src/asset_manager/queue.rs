@@ -0,0 +1,87 @@
+use super::AssetManager;
+use crate::events::AssetEvent;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Threads reading and decoding the queued assets.
+const WORKERS: usize = 4;
+
+/// Change made to the asset manager on the main thread, once a worker is
+/// done with a step of a job.
+pub type Apply =
+    Box<dyn FnOnce(&mut AssetManager) -> Option<AssetEvent> + Send>;
+
+/// Asset to load on a worker thread.
+pub struct Job {
+    /// Applied when a worker picks the job.
+    pub started: Apply,
+    /// Does the work, and returns what to apply once it is done.
+    pub run: Box<dyn FnOnce() -> Apply + Send>,
+}
+
+/// Jobs shared by a bounded pool of worker threads, started with the first
+/// job.
+pub struct LoadQueue {
+    jobs: Option<Sender<Job>>,
+    sender: Sender<Apply>,
+    receiver: Receiver<Apply>,
+}
+
+impl Default for LoadQueue {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+
+        Self {
+            jobs: None,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl LoadQueue {
+    pub fn push(&mut self, job: Job) {
+        let sender = &self.sender;
+        let jobs = self.jobs.get_or_insert_with(|| Self::start(sender));
+
+        // Workers only stop once the queue is dropped.
+        jobs.send(job).expect("Asset workers stopped.");
+    }
+
+    fn start(sender: &Sender<Apply>) -> Sender<Job> {
+        let (jobs, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for _ in 0..WORKERS {
+            let receiver = Arc::clone(&receiver);
+            let sender = sender.clone();
+
+            thread::spawn(move || loop {
+                let job = match receiver.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+
+                // The manager is gone when sending fails.
+                if sender.send(job.started).is_err()
+                    || sender.send((job.run)()).is_err()
+                {
+                    return;
+                }
+            });
+        }
+
+        jobs
+    }
+
+    /// What the workers are done with so far.
+    pub fn done(&self) -> impl Iterator<Item = Apply> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Wait for the next step of a job.
+    pub fn wait(&self) -> Option<Apply> {
+        self.receiver.recv().ok()
+    }
+}
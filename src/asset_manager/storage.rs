@@ -0,0 +1,284 @@
+use super::Handle;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Where an asset loaded in the background is at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadState {
+    Queued,
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+struct Entry<T> {
+    name: String,
+    // Missing until the asset is loaded.
+    asset: Option<T>,
+    state: LoadState,
+    // Every handle holds a clone, the asset is unused once only this one
+    // is left.
+    refs: Arc<()>,
+}
+
+struct Slot<T> {
+    // Bumped each time the slot is freed, so old handles find nothing.
+    generation: u32,
+    entry: Option<Entry<T>>,
+}
+
+/// Assets of one type, stored in slots which are reused once freed.
+pub struct AssetStorage<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    names: HashMap<String, usize>,
+}
+
+impl<T> Default for AssetStorage<T> {
+    fn default() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<T> AssetStorage<T> {
+    /// Store an asset under a name, the asset it replaces is returned.
+    pub fn insert(&mut self, name: &str, asset: T) -> (Handle<T>, Option<T>) {
+        let (handle, replaced) = self.reserve(name);
+        self.set(&handle, Ok(asset));
+        (handle, replaced)
+    }
+
+    /// Keep a slot for an asset which isn't loaded yet, see `set`.
+    pub fn reserve(&mut self, name: &str) -> (Handle<T>, Option<T>) {
+        let replaced = self.remove(name);
+        let index = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                generation: 0,
+                entry: None,
+            });
+            self.slots.len() - 1
+        });
+
+        let refs = Arc::new(());
+        let slot = &mut self.slots[index];
+        slot.entry = Some(Entry {
+            name: String::from(name),
+            asset: None,
+            state: LoadState::Queued,
+            refs: Arc::clone(&refs),
+        });
+        self.names.insert(String::from(name), index);
+
+        (Handle::new(index, slot.generation, refs), replaced)
+    }
+
+    /// A new handle to the asset stored under this name.
+    pub fn handle(&self, name: &str) -> Option<Handle<T>> {
+        let index = *self.names.get(name)?;
+        let slot = &self.slots[index];
+        let entry = slot.entry.as_ref()?;
+
+        Some(Handle::new(index, slot.generation, Arc::clone(&entry.refs)))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        self.entry(handle)?.asset.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        self.entry_mut(handle)?.asset.as_mut()
+    }
+
+    pub fn state(&self, handle: &Handle<T>) -> Option<&LoadState> {
+        self.entry(handle).map(|entry| &entry.state)
+    }
+
+    pub fn set_state(&mut self, handle: &Handle<T>, state: LoadState) {
+        if let Some(entry) = self.entry_mut(handle) {
+            entry.state = state;
+        }
+    }
+
+    /// Put the loaded asset in its slot, or mark it as failed.
+    /// The asset it replaces is returned.
+    pub fn set(
+        &mut self,
+        handle: &Handle<T>,
+        asset: Result<T, String>,
+    ) -> Option<T> {
+        let entry = self.entry_mut(handle)?;
+
+        match asset {
+            Ok(asset) => {
+                entry.state = LoadState::Loaded;
+                entry.asset.replace(asset)
+            }
+            Err(err) => {
+                entry.state = LoadState::Failed(err);
+                None
+            }
+        }
+    }
+
+    /// Loaded assets, with their name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.entry.as_ref())
+            .filter_map(|entry| {
+                Some((entry.name.as_str(), entry.asset.as_ref()?))
+            })
+    }
+
+    pub fn get_mut_by_name(&mut self, name: &str) -> Option<&mut T> {
+        let index = *self.names.get(name)?;
+        self.slots[index]
+            .entry
+            .as_mut()
+            .and_then(|entry| entry.asset.as_mut())
+    }
+
+    /// Free the slot of the asset, its handles find nothing anymore.
+    pub fn remove(&mut self, name: &str) -> Option<T> {
+        let index = self.names.remove(name)?;
+        let slot = &mut self.slots[index];
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        slot.entry.take().and_then(|entry| entry.asset)
+    }
+
+    /// Remove the assets no handle refers to anymore.
+    pub fn remove_unused(&mut self) -> Vec<T> {
+        let unused: Vec<String> = self
+            .slots
+            .iter()
+            .filter_map(|slot| slot.entry.as_ref())
+            .filter(|entry| Arc::strong_count(&entry.refs) == 1)
+            .map(|entry| entry.name.clone())
+            .collect();
+
+        unused.iter().filter_map(|name| self.remove(name)).collect()
+    }
+
+    fn entry(&self, handle: &Handle<T>) -> Option<&Entry<T>> {
+        match self.slots.get(handle.index) {
+            Some(slot) if slot.generation == handle.generation => {
+                slot.entry.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    fn entry_mut(&mut self, handle: &Handle<T>) -> Option<&mut Entry<T>> {
+        match self.slots.get_mut(handle.index) {
+            Some(slot) if slot.generation == handle.generation => {
+                slot.entry.as_mut()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_asset_from_handle() {
+        let mut storage = AssetStorage::default();
+        let (handle, replaced) = storage.insert("a.png", 1);
+
+        assert_eq!(replaced, None);
+        assert_eq!(storage.get(&handle), Some(&1));
+        assert_eq!(storage.handle("a.png"), Some(handle));
+        assert_eq!(storage.handle("b.png"), None);
+    }
+
+    #[test]
+    fn should_reuse_freed_slots() {
+        let mut storage = AssetStorage::default();
+        let (a, _) = storage.insert("a.png", 1);
+        storage.insert("b.png", 2);
+
+        assert_eq!(storage.remove("a.png"), Some(1));
+        let (c, _) = storage.insert("c.png", 3);
+
+        // Same slot, but the old handle doesn't see the new asset.
+        assert_eq!(c.index, a.index);
+        assert_eq!(storage.get(&a), None);
+        assert_eq!(storage.get(&c), Some(&3));
+        assert_eq!(storage.get_mut_by_name("b.png"), Some(&mut 2));
+    }
+
+    #[test]
+    fn should_replace_asset_with_same_name() {
+        let mut storage = AssetStorage::default();
+        let (a, _) = storage.insert("a.png", 1);
+        let (b, replaced) = storage.insert("a.png", 2);
+
+        assert_eq!(replaced, Some(1));
+        assert_eq!(storage.get(&a), None);
+        assert_eq!(storage.get(&b), Some(&2));
+    }
+
+    #[test]
+    fn should_fill_reserved_slot() {
+        let mut storage = AssetStorage::default();
+        let (a, _) = storage.reserve("a.png");
+        let (b, _) = storage.reserve("b.png");
+
+        assert_eq!(storage.get(&a), None);
+        assert_eq!(storage.state(&a), Some(&LoadState::Queued));
+
+        storage.set(&a, Ok(1));
+        storage.set(&b, Err(String::from("not an image")));
+
+        assert_eq!(storage.get(&a), Some(&1));
+        assert_eq!(storage.state(&a), Some(&LoadState::Loaded));
+        assert_eq!(storage.get(&b), None);
+        assert_eq!(
+            storage.state(&b),
+            Some(&LoadState::Failed(String::from("not an image")))
+        );
+    }
+
+    #[test]
+    fn should_iter_loaded_assets() {
+        let mut storage = AssetStorage::default();
+        storage.insert("a.png", 1);
+        storage.reserve("b.png");
+        storage.insert("c.png", 3);
+        storage.remove("a.png");
+
+        let assets: Vec<(&str, &i32)> = storage.iter().collect();
+        assert_eq!(assets, vec![("c.png", &3)]);
+    }
+
+    #[test]
+    fn should_remove_unused_assets() {
+        let mut storage = AssetStorage::default();
+        let (a, _) = storage.insert("a.png", 1);
+        let (b, _) = storage.insert("b.png", 2);
+        let weak = b.downgrade();
+        let copy = a.clone();
+
+        drop(a);
+        drop(b);
+        assert_eq!(storage.remove_unused(), vec![2]);
+        assert!(weak.upgrade().is_none());
+
+        assert_eq!(storage.get(&copy), Some(&1));
+        drop(copy);
+        assert_eq!(storage.remove_unused(), vec![1]);
+        assert!(!storage.contains("a.png"));
+    }
+}
@@ -0,0 +1,93 @@
+use super::{AssetLoader, LoadContext, Ressource};
+use crate::{constants::TEXTURE_PATH, opengl::OpenGL};
+use image::{self, GenericImageView, ImageFormat};
+
+/// This is used by opengl.
+/// If None, this asset is currently not yet send into the GPU.
+type GlId = Option<u32>;
+
+// All images are converted in rgba.
+#[derive(Debug)]
+pub struct Texture {
+    pub raw: Vec<u8>,
+    pub width: i32,
+    pub height: i32,
+    pub gl_id: GlId,
+}
+
+impl Ressource for Texture {
+    const DIRECTORY: &'static str = TEXTURE_PATH;
+
+    fn gl_load(&mut self) {
+        // We want to sent the texture only once into the GPU.
+        if self.gl_id.is_some() {
+            return;
+        }
+
+        // This id is used to activate the texture in the renderer system.
+        self.gl_id =
+            Some(OpenGL::load_2d_texture(self.width, self.height, &self.raw));
+    }
+
+    // Meshes keep the same GL id, only its pixels change.
+    fn reload(&mut self, mut new: Self) {
+        new.gl_id = self.gl_id;
+        if let Some(id) = new.gl_id {
+            OpenGL::update_2d_texture(id, new.width, new.height, &new.raw);
+        }
+        *self = new;
+    }
+
+    fn release(self) {
+        if let Some(id) = self.gl_id {
+            OpenGL::delete_texture(id);
+        }
+    }
+}
+
+/// Decode the images supported by the `image` crate.
+/// The format is guessed from the content of the file, or from its
+/// extension for the ones without a signature.
+pub struct TextureLoader;
+
+impl TextureLoader {
+    fn format(bytes: &[u8], ctx: &LoadContext) -> Result<ImageFormat, String> {
+        if let Ok(format) = image::guess_format(bytes) {
+            return Ok(format);
+        }
+
+        match ctx.extension().as_deref() {
+            Some("tga") => Ok(ImageFormat::TGA),
+            Some("hdr") => Ok(ImageFormat::HDR),
+            Some("pbm") | Some("pgm") | Some("ppm") | Some("pam") => {
+                Ok(ImageFormat::PNM)
+            }
+            _ => Err(format!("{}: unknown image format", ctx.path)),
+        }
+    }
+}
+
+impl AssetLoader for TextureLoader {
+    type Asset = Texture;
+
+    fn extensions(&self) -> &[&str] {
+        &[
+            "png", "jpg", "jpeg", "gif", "bmp", "ico", "tif", "tiff", "webp",
+            "tga", "hdr", "pbm", "pgm", "ppm", "pam",
+        ]
+    }
+
+    fn load(&self, bytes: &[u8], ctx: &LoadContext) -> Result<Texture, String> {
+        let format = Self::format(bytes, ctx)?;
+        let texture = image::load_from_memory_with_format(bytes, format)
+            .map_err(|err| err.to_string())?;
+        let (width, height) = texture.dimensions();
+
+        Ok(Texture {
+            raw: texture.to_rgba().into_raw(),
+            width: width as i32,
+            height: height as i32,
+            gl_id: None,
+        })
+    }
+}
@@ -1,8 +1,9 @@
 use nalgebra_glm as glm;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Camera {
     pub pos: (f64, f64),
     pub speed: f64,
@@ -1,38 +1,151 @@
+use crate::{
+    ecs::{self, Restorable},
+    resources::PhysicWorld,
+};
 use nphysics3d::{
     material::{BasicMaterial, MaterialHandle},
-    object::{ColliderDesc, ColliderHandle},
+    object::{ColliderDesc as NColliderDesc, ColliderHandle},
     world::World,
 };
 
 use nalgebra_glm as glm;
-use ncollide3d::shape::ShapeHandle;
+use ncollide3d::shape::{Ball, Cuboid, ShapeHandle};
+use serde::{Deserialize, Serialize};
+
+type Vector3 = glm::TVec3<f32>;
 
 #[derive(Debug)]
 pub struct Collider {
     handle: ColliderHandle,
+    /// Kept to build the collider again.
+    desc: ColliderDesc,
+    transform: Vector3,
 }
 
 impl Collider {
     pub fn new(
         mut world: &mut World<f32>,
-        shape: ShapeHandle<f32>,
-        transform: glm::TVec3<f32>,
-        density: f32,
+        desc: &ColliderDesc,
+        transform: Vector3,
     ) -> Self {
-        let handle = ColliderDesc::new(shape)
+        let handle = NColliderDesc::new(desc.shape())
             .translation(transform)
-            .material(MaterialHandle::new(BasicMaterial::new(0.3, 0.)))
-            .density(density)
+            .material(desc.material.handle())
+            .density(1.)
             .build(&mut world)
             .handle();
 
-        Self { handle }
+        Self {
+            handle,
+            desc: desc.clone(),
+            transform,
+        }
+    }
+
+    pub fn handle(&self) -> ColliderHandle {
+        self.handle
+    }
+
+    /// Remove the collider from the physic world.
+    pub fn release(self, world: &mut World<f32>) {
+        world.remove_colliders(&[self.handle]);
+    }
+}
+
+/// What a collider standing alone is built from, saved in world snapshots.
+#[derive(Debug, Clone)]
+pub struct ColliderState {
+    desc: ColliderDesc,
+    transform: Vector3,
+}
+
+impl Restorable for Collider {
+    type State = ColliderState;
+
+    fn save(&self, _: &ecs::World) -> ColliderState {
+        ColliderState {
+            desc: self.desc.clone(),
+            transform: self.transform,
+        }
+    }
+
+    // A collider standing alone can't be moved, it is built again.
+    fn restore(&mut self, state: &ColliderState, world: &ecs::World) {
+        if self.desc != state.desc || self.transform != state.transform {
+            let mut physic_world = world.resource_mut::<PhysicWorld>();
+            physic_world.remove_colliders(&[self.handle]);
+            *self = Self::new(&mut physic_world, &state.desc, state.transform);
+        }
+    }
+
+    fn rebuild(state: &ColliderState, world: &ecs::World) -> Option<Self> {
+        let mut physic_world = world.resource_mut::<PhysicWorld>();
+        Some(Self::new(&mut physic_world, &state.desc, state.transform))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Shapes {
+    Cuboid,
+    Ball,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl Material {
+    fn handle(self) -> MaterialHandle<f32> {
+        MaterialHandle::new(BasicMaterial::new(self.restitution, self.friction))
+    }
+}
+
+// Same as the default material of nphysics.
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            restitution: 0.,
+            friction: 0.5,
+        }
+    }
+}
+
+/// Collider as written in scene files.
+/// It is attached to the rigid body of the entity if it has one, or
+/// added alone to the physic world otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Collider")]
+pub struct ColliderDesc {
+    pub shape: Shapes,
+    /// Half extents of a cuboid, the radius of a ball is its `x`.
+    pub size: Vector3,
+    #[serde(default)]
+    pub material: Material,
+}
+
+impl ColliderDesc {
+    fn shape(&self) -> ShapeHandle<f32> {
+        match self.shape {
+            Shapes::Cuboid => ShapeHandle::new(Cuboid::new(self.size)),
+            Shapes::Ball => ShapeHandle::new(Ball::new(self.size.x)),
+        }
+    }
+
+    /// Collider of a rigid body, at the origin of the body.
+    pub fn attached(&self) -> NColliderDesc<f32> {
+        NColliderDesc::new(self.shape())
+            .material(self.material.handle())
+            .density(1.)
     }
 
-    pub fn simple(
-        shape: ShapeHandle<f32>,
+    /// Add the collider alone to the physic world.
+    pub fn build(
+        &self,
+        world: &mut World<f32>,
         transform: glm::TVec3<f32>,
-    ) -> ColliderDesc<f32> {
-        ColliderDesc::new(shape).translation(transform).density(1.)
+    ) -> Collider {
+        Collider::new(world, self, transform)
     }
 }
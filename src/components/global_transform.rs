@@ -0,0 +1,25 @@
+use crate::components::Transform;
+use nalgebra_glm as glm;
+
+/// World space transform of an entity, computed from its `Transform` and
+/// the ones of its parents by the `TransformPropagation` system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform(pub glm::Mat4);
+
+impl GlobalTransform {
+    pub fn position(&self) -> glm::TVec3<f32> {
+        glm::vec3(self.0[(0, 3)], self.0[(1, 3)], self.0[(2, 3)])
+    }
+}
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        GlobalTransform(glm::Mat4::identity())
+    }
+}
+
+impl From<&Transform> for GlobalTransform {
+    fn from(transform: &Transform) -> Self {
+        GlobalTransform(transform.matrix())
+    }
+}
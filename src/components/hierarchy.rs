@@ -0,0 +1,52 @@
+use crate::ecs::{EntityId, World};
+
+/// Entity this one is attached to, its `Transform` is relative to the
+/// parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parent(pub EntityId);
+
+/// Entities attached to this one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Children(pub Vec<EntityId>);
+
+/// Attach `child` to `parent`, detaching it from its previous parent if
+/// any.
+pub fn set_parent(world: &mut World, child: EntityId, parent: EntityId) {
+    remove_parent(world, child);
+    world.insert(child, Parent(parent));
+
+    let attached = world
+        .query::<&mut Children>()
+        .get(parent)
+        .map(|mut children| children.0.push(child))
+        .is_some();
+
+    if !attached {
+        world.insert(parent, Children(vec![child]));
+    }
+}
+
+/// Detach `child` from its parent, if it has one.
+pub fn remove_parent(world: &mut World, child: EntityId) {
+    if let Some(Parent(parent)) = world.take::<Parent>(child) {
+        if let Some(mut children) = world.query::<&mut Children>().get(parent) {
+            children.0.retain(|id| *id != child);
+        }
+    }
+}
+
+/// Despawn an entity along with all its descendants.
+#[allow(unused)]
+pub fn despawn_recursive(world: &mut World, id: EntityId) {
+    let children = world
+        .query::<&Children>()
+        .get(id)
+        .map(|children| children.0.clone())
+        .unwrap_or_default();
+
+    children
+        .into_iter()
+        .for_each(|child| despawn_recursive(world, child));
+
+    world.despawn(id);
+}
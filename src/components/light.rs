@@ -1,6 +1,6 @@
-use crate::{components::Transform, opengl::OpenGL};
+use crate::{components::GlobalTransform, opengl::OpenGL};
 use nalgebra_glm as glm;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::mem;
 
 static mut UBO_LIGHT_INDEX: usize = 0;
@@ -12,7 +12,7 @@ type Vector3 = glm::TVec3<f32>;
 // 3 -> Point light
 // 4 -> Spot light
 // let l_type = 1;
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Lights {
     Sun,
     Directional,
@@ -20,8 +20,9 @@ pub enum Lights {
     Spotlight,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Light {
+    #[serde(skip)]
     ubo_index: usize,
     pub kind: Lights,
     pub direction: Vector3,
@@ -62,7 +63,17 @@ impl Light {
         }
     }
 
-    pub fn set_to_shader(&self, lights_ubo: u32, transform: &Transform) {
+    /// Slot of the light in the lights uniform block.
+    pub fn ubo_index(&self) -> usize {
+        self.ubo_index
+    }
+
+    /// Take the slot of a light this one replaces.
+    pub fn set_ubo_index(&mut self, ubo_index: usize) {
+        self.ubo_index = ubo_index;
+    }
+
+    pub fn set_to_shader(&self, lights_ubo: u32, transform: &GlobalTransform) {
         let size = mem::size_of::<glm::TVec4<f32>>() as isize;
         let block_offset = 96 * self.ubo_index as isize;
 
@@ -79,7 +90,7 @@ impl Light {
             size + block_offset,
         );
         OpenGL::set_vec3_to_ubo(
-            transform.position,
+            transform.position(),
             lights_ubo,
             2 * size + block_offset,
         );
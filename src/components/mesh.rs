@@ -1,69 +1,206 @@
-use crate::opengl::OpenGL;
+use crate::{
+    asset_manager::{AssetManager, Handle, Texture},
+    ecs::{Restorable, World},
+    opengl::OpenGL,
+    shader::Shader,
+};
+use serde::{Deserialize, Serialize};
 use std::default::Default;
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Primitives {
     Plane,
     Cube,
 }
 
+#[cfg_attr(rustfmt, rustfmt_skip)]
+const PLANE: [f32; 48] = [
+    // vertex, tex, normal.
+    -1., 0., 1., 0., 5., 0., 1., 0.,
+    1., 0., -1., 5., 0.,0., 1., 0.,
+    1., 0., 1., 5., 5., 0., 1., 0.,
+    -1., 0., 1., 0., 5.,0., 1., 0.,
+    -1., 0., -1., 0., 0.,0., 1., 0.,
+    1., 0., -1., 5., 0.,0., 1., 0.,
+];
+
+// Position, texture coordinate and normal.
+const CUBE: [f32; 288] = [
+    -0.5, -0.5, -0.5, 0.0, 0.0, 0., 0., -1., 0.5, -0.5, -0.5, 1.0, 0.0, 0., 0.,
+    -1., 0.5, 0.5, -0.5, 1.0, 1.0, 0., 0., -1., 0.5, 0.5, -0.5, 1.0, 1.0, 0.,
+    0., -1., -0.5, 0.5, -0.5, 0.0, 1.0, 0., 0., -1., -0.5, -0.5, -0.5, 0.0,
+    0.0, 0., 0., -1., -0.5, -0.5, 0.5, 0.0, 0.0, 0., 0., 1., 0.5, -0.5, 0.5,
+    1.0, 0.0, 0., 0., 1., 0.5, 0.5, 0.5, 1.0, 1.0, 0., 0., 1., 0.5, 0.5, 0.5,
+    1.0, 1.0, 0., 0., 1., -0.5, 0.5, 0.5, 0.0, 1.0, 0., 0., 1., -0.5, -0.5,
+    0.5, 0.0, 0.0, 0., 0., 1., -0.5, 0.5, 0.5, 1.0, 0.0, -1., 0., 0., -0.5,
+    0.5, -0.5, 1.0, 1.0, -1., 0., 0., -0.5, -0.5, -0.5, 0.0, 1.0, -1., 0., 0.,
+    -0.5, -0.5, -0.5, 0.0, 1.0, -1., 0., 0., -0.5, -0.5, 0.5, 0.0, 0.0, -1.,
+    0., 0., -0.5, 0.5, 0.5, 1.0, 0.0, -1., 0., 0., 0.5, 0.5, 0.5, 1.0, 0.0, 1.,
+    0., 0., 0.5, 0.5, -0.5, 1.0, 1.0, 1., 0., 0., 0.5, -0.5, -0.5, 0.0, 1.0,
+    1., 0., 0., 0.5, -0.5, -0.5, 0.0, 1.0, 1., 0., 0., 0.5, -0.5, 0.5, 0.0,
+    0.0, 1., 0., 0., 0.5, 0.5, 0.5, 1.0, 0.0, 1., 0., 0., -0.5, -0.5, -0.5,
+    0.0, 1.0, 0., -1., 0., 0.5, -0.5, -0.5, 1.0, 1.0, 0., -1., 0., 0.5, -0.5,
+    0.5, 1.0, 0.0, 0., -1., 0., 0.5, -0.5, 0.5, 1.0, 0.0, 0., -1., 0., -0.5,
+    -0.5, 0.5, 0.0, 0.0, 0., -1., 0., -0.5, -0.5, -0.5, 0.0, 1.0, 0., -1., 0.,
+    -0.5, 0.5, -0.5, 0.0, 1.0, 0., 1., 0., 0.5, 0.5, -0.5, 1.0, 1.0, 0., 1.,
+    0., 0.5, 0.5, 0.5, 1.0, 0.0, 0., 1., 0., 0.5, 0.5, 0.5, 1.0, 0.0, 0., 1.,
+    0., -0.5, 0.5, 0.5, 0.0, 0.0, 0., 1., 0., -0.5, 0.5, -0.5, 0.0, 1.0, 0.,
+    1., 0.,
+];
+
 #[derive(Debug)]
-pub struct Mesh<'a> {
+pub struct Mesh {
+    pub primitive: Primitives,
     pub vao: u32,
     pub lines: i32,
     pub has_ebo: bool,
-    pub texture: Option<String>,
-    pub shader: &'a str,
+    /// The texture and the shader stay loaded as long as the mesh exists.
+    pub texture: Option<Handle<Texture>>,
+    pub shader: Handle<Shader>,
     pub color: (f32, f32, f32),
 }
 
-impl<'a> Mesh<'a> {
+impl Mesh {
     pub fn new(
         prim: Primitives,
-        texture: Option<String>,
-        shader: &'a str,
+        texture: Option<Handle<Texture>>,
+        shader: Handle<Shader>,
     ) -> Self {
-        let (vao, lines, has_ebo) = Mesh::get_gl_info(prim);
+        Self::from_data(&MeshData::new(prim), texture, shader)
+    }
 
+    /// Send vertices built beforehand to the GPU.
+    pub fn from_data(
+        data: &MeshData,
+        texture: Option<Handle<Texture>>,
+        shader: Handle<Shader>,
+    ) -> Self {
         Self {
-            shader,
-            vao,
-            lines,
-            has_ebo,
+            primitive: data.primitive,
+            vao: OpenGL::load_mesh(&data.vertices),
+            lines: data.lines(),
+            has_ebo: false,
             texture,
-            ..Self::default()
+            shader,
+            color: (1., 1., 1.),
         }
     }
 
-    pub fn get_gl_info(prim: Primitives) -> (u32, i32, bool) {
-        match prim {
-            Primitives::Plane => OpenGL::gen_plane(),
-            Primitives::Cube => OpenGL::gen_cube(),
-        }
+    /// Delete the vertex array from the GPU.
+    pub fn release(self) {
+        OpenGL::delete_vao(self.vao);
     }
 
     pub fn get_vao(&self) -> u32 {
         self.vao
     }
+}
+
+/// What a mesh is built from, saved in world snapshots.
+#[derive(Debug, Clone)]
+pub struct MeshState {
+    primitive: Primitives,
+    texture: Option<Handle<Texture>>,
+    shader: Handle<Shader>,
+    color: (f32, f32, f32),
+}
+
+impl Restorable for Mesh {
+    type State = MeshState;
+
+    fn save(&self, _: &World) -> MeshState {
+        MeshState {
+            primitive: self.primitive,
+            texture: self.texture.clone(),
+            shader: self.shader.clone(),
+            color: self.color,
+        }
+    }
+
+    fn restore(&mut self, state: &MeshState, _: &World) {
+        // The vertices are sent again if the primitive changed.
+        if self.primitive != state.primitive {
+            let data = MeshData::new(state.primitive);
+            OpenGL::delete_vao(self.vao);
+            self.primitive = state.primitive;
+            self.vao = OpenGL::load_mesh(&data.vertices);
+            self.lines = data.lines();
+        }
+
+        self.texture = state.texture.clone();
+        self.shader = state.shader.clone();
+        self.color = state.color;
+    }
+
+    fn rebuild(state: &MeshState, _: &World) -> Option<Self> {
+        let mut mesh = Self::new(
+            state.primitive,
+            state.texture.clone(),
+            state.shader.clone(),
+        );
+        mesh.color = state.color;
 
-    pub fn get_texture(&self) -> Option<&String> {
-        self.texture.as_ref()
+        Some(mesh)
     }
 }
 
-// The default function will create a simple cube mesh without
-// with only a color.
-impl<'a> Default for Mesh<'a> {
-    fn default() -> Self {
-        let (vao, lines, has_ebo) = OpenGL::gen_cube();
-        let shader = "default";
+/// Vertices of a mesh, built on the CPU so they can be prepared away from
+/// the thread owning the GL context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshData {
+    pub primitive: Primitives,
+    pub vertices: Vec<f32>,
+}
+
+impl MeshData {
+    pub fn new(prim: Primitives) -> Self {
+        let vertices = match prim {
+            Primitives::Plane => PLANE.to_vec(),
+            Primitives::Cube => CUBE.to_vec(),
+        };
 
         Self {
-            vao,
-            has_ebo,
-            lines,
-            texture: None,
-            shader,
-            color: (1., 1., 1.),
+            primitive: prim,
+            vertices,
         }
     }
+
+    /// Number of vertices to draw.
+    pub fn lines(&self) -> i32 {
+        (self.vertices.len() / 8) as i32
+    }
+}
+
+/// Mesh as written in scene files, the GPU one is built from it when the
+/// scene is loaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Mesh")]
+pub struct MeshDesc {
+    pub primitive: Primitives,
+    #[serde(default)]
+    pub texture: Option<String>,
+    pub shader: String,
+}
+
+impl MeshDesc {
+    /// Queue the texture if any, and send the vertices to the GPU.
+    /// The mesh is drawn without its texture until it is loaded.
+    pub fn build(&self, asset_manager: &mut AssetManager) -> Mesh {
+        let texture = self
+            .texture
+            .as_ref()
+            .map(|texture| asset_manager.load::<Texture>(texture));
+
+        Mesh::new(self.primitive, texture, self.load_shader(asset_manager))
+    }
+
+    /// The program of the asset manager, or one compiled from the sources
+    /// of the shader directory with the same name.
+    pub fn load_shader(
+        &self,
+        asset_manager: &mut AssetManager,
+    ) -> Handle<Shader> {
+        let name = self.shader.as_str();
+        asset_manager.add_shader(name, name, name)
+    }
 }
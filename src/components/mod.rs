@@ -1,5 +1,7 @@
 mod camera;
 mod collider;
+mod global_transform;
+mod hierarchy;
 mod light;
 mod mesh;
 mod player;
@@ -8,6 +10,8 @@ mod transform;
 
 pub use camera::*;
 pub use collider::*;
+pub use global_transform::*;
+pub use hierarchy::*;
 pub use light::*;
 pub use mesh::*;
 pub use player::*;
@@ -1,28 +1,48 @@
+use super::ColliderDesc;
+use crate::{
+    ecs::{self, Restorable},
+    resources::PhysicWorld,
+};
 use nalgebra_glm as glm;
 use nphysics3d::{
-    math::Velocity,
+    math::{Isometry, Velocity},
     object::{
-        Body, BodyHandle, BodyStatus, ColliderDesc, RigidBody as NRigidBody,
-        RigidBodyDesc,
+        Body, BodyHandle, BodyStatus, RigidBody as NRigidBody,
+        RigidBodyDesc as NRigidBodyDesc,
     },
     world::World,
 };
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub struct RigidBody {
     mass: f32,
+    /// Kept to build the body again.
+    collider: Option<ColliderDesc>,
     handle: BodyHandle,
 }
 
+/// State of a body in the physic world, saved in world snapshots, with
+/// what it is built from.
+#[derive(Debug, Clone)]
+pub struct BodyState {
+    position: Isometry<f32>,
+    velocity: Velocity<f32>,
+    status: BodyStatus,
+    mass: f32,
+    collider: Option<ColliderDesc>,
+}
+
 impl RigidBody {
     pub fn new(
         mut world: &mut World<f32>,
         mass: f32,
         transform: glm::TVec3<f32>,
         status: BodyStatus,
-        collider: Option<ColliderDesc<f32>>,
+        collider: Option<&ColliderDesc>,
     ) -> Self {
-        let mut body = RigidBodyDesc::new()
+        let attached = collider.map(ColliderDesc::attached);
+        let mut body = NRigidBodyDesc::new()
             .mass(mass)
             .translation(transform)
             .status(status);
@@ -31,8 +51,8 @@ impl RigidBody {
             body.set_velocity(Velocity::linear(0., 0., 0.));
         }
 
-        if let Some(collider) = &collider {
-            body.add_collider(collider);
+        if let Some(attached) = &attached {
+            body.add_collider(attached);
         };
 
         // Register this body in the physic world.
@@ -40,7 +60,20 @@ impl RigidBody {
         // Get the handle to retrieve this body later.
         let handle = b.handle();
 
-        Self { mass, handle }
+        Self {
+            mass,
+            collider: collider.cloned(),
+            handle,
+        }
+    }
+
+    pub fn handle(&self) -> BodyHandle {
+        self.handle
+    }
+
+    /// Remove the body and its colliders from the physic world.
+    pub fn release(self, world: &mut World<f32>) {
+        world.remove_bodies(&[self.handle]);
     }
 
     #[allow(unused)]
@@ -75,3 +108,90 @@ impl RigidBody {
             .expect("Handle not register in physic world")
     }
 }
+
+impl Restorable for RigidBody {
+    type State = BodyState;
+
+    fn save(&self, world: &ecs::World) -> BodyState {
+        let physic_world = world.resource::<PhysicWorld>();
+        let body = self.get(&physic_world);
+
+        BodyState {
+            position: *body.position(),
+            velocity: *body.velocity(),
+            status: body.status(),
+            mass: self.mass,
+            collider: self.collider.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: &BodyState, world: &ecs::World) {
+        let mut physic_world = world.resource_mut::<PhysicWorld>();
+        let body = self.get_mut(&mut physic_world);
+
+        body.set_position(state.position);
+        body.set_velocity(state.velocity);
+        body.set_status(state.status);
+        body.activate();
+    }
+
+    fn rebuild(state: &BodyState, world: &ecs::World) -> Option<Self> {
+        let mut rigid_body = Self::new(
+            &mut world.resource_mut::<PhysicWorld>(),
+            state.mass,
+            state.position.translation.vector,
+            state.status,
+            state.collider.as_ref(),
+        );
+        rigid_body.restore(state, world);
+
+        Some(rigid_body)
+    }
+}
+
+/// See `BodyStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    Disabled,
+    Static,
+    Dynamic,
+    Kinematic,
+}
+
+impl From<Status> for BodyStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Disabled => BodyStatus::Disabled,
+            Status::Static => BodyStatus::Static,
+            Status::Dynamic => BodyStatus::Dynamic,
+            Status::Kinematic => BodyStatus::Kinematic,
+        }
+    }
+}
+
+/// Rigid body as written in scene files, the collider of the entity is
+/// attached to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "RigidBody")]
+pub struct RigidBodyDesc {
+    pub mass: f32,
+    pub status: Status,
+}
+
+impl RigidBodyDesc {
+    /// Register the body in the physic world.
+    pub fn build(
+        &self,
+        world: &mut World<f32>,
+        transform: glm::TVec3<f32>,
+        collider: Option<&ColliderDesc>,
+    ) -> RigidBody {
+        RigidBody::new(
+            world,
+            self.mass,
+            transform,
+            self.status.into(),
+            collider,
+        )
+    }
+}
@@ -1,12 +1,12 @@
 use nalgebra_glm as glm;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::default::Default;
 
 use nalgebra;
 
 type Vector3 = glm::TVec3<f32>;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub position: Vector3,
     pub scale: Vector3,
@@ -22,6 +22,19 @@ impl Transform {
             ..Self::default()
         }
     }
+
+    /// Local model matrix: translation, rotation then scale.
+    pub fn matrix(&self) -> glm::Mat4 {
+        let mut model = glm::Mat4::identity();
+
+        model = glm::translate(&model, &self.position);
+
+        if let Some((axis, angle)) = self.rotation.axis_angle() {
+            model = glm::rotate(&model, angle, &axis);
+        }
+
+        glm::scale(&model, &self.scale)
+    }
 }
 
 impl Default for Transform {
@@ -1,6 +1,14 @@
 pub const GAME_TITLE: &str = "[WIP] Game Engine";
 pub const SCREEN_WIDTH: f32 = 1200.;
 pub const SCREEN_HEIGHT: f32 = 1200.;
-pub const SCENE_PATH: &str = "assets/scenes/";
-pub const SHADER_PATH: &str = "assets/shaders/";
-pub const TEXTURE_PATH: &str = "assets/textures/";
+// Asset directory on disk, or pack file bundling it, see `Vfs::assets`.
+pub const ASSETS_PATH: &str = "assets/";
+pub const ASSETS_PACK: &str = "assets.pack";
+// Directories of the virtual file system.
+pub const PREFAB_PATH: &str = "prefabs/";
+pub const SCENE_PATH: &str = "scenes/";
+pub const SHADER_PATH: &str = "shaders/";
+pub const TEXTURE_PATH: &str = "textures/";
+// Binding points of the uniform blocks shared by every shader.
+pub const CAMERA_UBO_BINDING: u32 = 0;
+pub const LIGHTS_UBO_BINDING: u32 = 1;
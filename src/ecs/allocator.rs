@@ -0,0 +1,155 @@
+use std::fmt;
+
+/// Runtime handle of an entity.
+///
+/// The index is the slot of the entity, the generation is bumped each
+/// time the slot is reused, so a stale handle never points to the entity
+/// reusing its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+impl EntityId {
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// Hand out entity handles and recycle the freed slots.
+/// Each `World` owns its allocator.
+#[derive(Default, Debug)]
+pub struct EntityAllocator {
+    // Generation of the last entity of each slot, it never goes back.
+    generations: Vec<u32>,
+    // Slot is alive or not.
+    alive: Vec<bool>,
+    free: Vec<u32>,
+}
+
+impl EntityAllocator {
+    pub fn allocate(&mut self) -> EntityId {
+        match self.free.pop() {
+            Some(index) => {
+                let generation =
+                    self.generations[index as usize].wrapping_add(1);
+                self.generations[index as usize] = generation;
+                self.alive[index as usize] = true;
+
+                EntityId { index, generation }
+            }
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                self.alive.push(true);
+
+                EntityId {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Free the slot of the given entity.
+    /// Returns false if the handle was already stale.
+    pub fn free(&mut self, id: EntityId) -> bool {
+        if !self.is_alive(id) {
+            return false;
+        }
+
+        self.alive[id.index()] = false;
+        self.free.push(id.index);
+
+        true
+    }
+
+    /// Bring a freed handle back to life, e.g. when restoring a snapshot.
+    /// Returns false if its slot was reused since, the handle may be
+    /// held by stale references to another entity then.
+    pub fn revive(&mut self, id: EntityId) -> bool {
+        let index = id.index();
+
+        if index >= self.generations.len()
+            || self.alive[index]
+            || self.generations[index] != id.generation
+        {
+            return false;
+        }
+
+        self.free.retain(|free| *free != id.index);
+        self.alive[index] = true;
+
+        true
+    }
+
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        let index = id.index();
+
+        index < self.generations.len()
+            && self.alive[index]
+            && self.generations[index] == id.generation
+    }
+
+    /// Number of living entities.
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_recycle_freed_slots() {
+        let mut allocator = EntityAllocator::default();
+
+        let a = allocator.allocate();
+        let b = allocator.allocate();
+        assert_ne!(a, b);
+        assert_eq!(allocator.len(), 2);
+
+        assert!(allocator.free(a));
+        assert!(!allocator.is_alive(a));
+        assert!(!allocator.free(a));
+
+        let c = allocator.allocate();
+        assert_eq!(c.index(), a.index());
+        assert_eq!(c.generation(), a.generation() + 1);
+        assert!(allocator.is_alive(c));
+        assert!(!allocator.is_alive(a));
+        assert!(allocator.is_alive(b));
+    }
+
+    #[test]
+    fn should_revive_handles_of_unused_slots_only() {
+        let mut allocator = EntityAllocator::default();
+
+        let a = allocator.allocate();
+        allocator.free(a);
+        assert!(allocator.revive(a));
+        assert!(allocator.is_alive(a));
+        assert!(!allocator.revive(a));
+
+        allocator.free(a);
+        let b = allocator.allocate();
+        allocator.free(b);
+        assert!(!allocator.revive(a));
+
+        let c = allocator.allocate();
+        assert_eq!(c.index(), a.index());
+        assert!(c.generation() > b.generation());
+    }
+}
@@ -0,0 +1,282 @@
+use super::{borrow::AtomicBorrow, ComponentTicks, EntityId};
+use std::any::{type_name, Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ptr::NonNull;
+
+/// Components of one entity, sorted by type, each one in its own column.
+pub type Components = Vec<(TypeId, Box<dyn Column>)>;
+
+/// A type-erased column, backed by a plain `Vec<T>`.
+/// Every component of the same type in an archetype lives contiguously
+/// in one column.
+pub trait Column: Any + Send + Sync {
+    fn len(&self) -> usize;
+
+    /// Create an empty column storing the same component type.
+    fn new_empty(&self) -> Box<dyn Column>;
+
+    /// Move the component at `row` at the end of `other`.
+    /// The last component of this column takes its place.
+    fn move_row(&mut self, row: usize, other: &mut dyn Column);
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Send + Sync + 'static> Column for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn new_empty(&self) -> Box<dyn Column> {
+        Box::new(Vec::<T>::new())
+    }
+
+    fn move_row(&mut self, row: usize, other: &mut dyn Column) {
+        let component = self.swap_remove(row);
+
+        other
+            .as_any_mut()
+            .downcast_mut::<Vec<T>>()
+            .expect("Columns don't store the same type.")
+            .push(component);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A column guarded by a borrow flag, so queries can hand out references
+/// to components from a shared `&World`.
+/// The change ticks of each component are stored next to it, guarded by
+/// the same flag.
+struct ColumnCell {
+    borrow: AtomicBorrow,
+    data: UnsafeCell<Box<dyn Column>>,
+    ticks: UnsafeCell<Vec<ComponentTicks>>,
+}
+
+impl ColumnCell {
+    fn new(column: Box<dyn Column>) -> Self {
+        Self {
+            borrow: AtomicBorrow::default(),
+            data: UnsafeCell::new(column),
+            ticks: UnsafeCell::new(vec![]),
+        }
+    }
+
+    fn get(&self) -> &dyn Column {
+        unsafe { (*self.data.get()).as_ref() }
+    }
+
+    fn get_mut(&mut self) -> &mut dyn Column {
+        self.data.get_mut().as_mut()
+    }
+}
+
+/// Store all entities sharing the exact same set of component types.
+pub struct Archetype {
+    types: Vec<TypeId>,
+    entities: Vec<EntityId>,
+    columns: HashMap<TypeId, ColumnCell>,
+}
+
+impl Archetype {
+    /// Create an archetype from a list of (sorted) columns, used
+    /// as a template for the type of each column.
+    pub fn new(template: &[(TypeId, Box<dyn Column>)]) -> Self {
+        let mut types = vec![];
+        let mut columns = HashMap::new();
+
+        template.iter().for_each(|(ty, column)| {
+            types.push(*ty);
+            columns.insert(*ty, ColumnCell::new(column.new_empty()));
+        });
+
+        Self {
+            types,
+            entities: vec![],
+            columns,
+        }
+    }
+
+    pub fn types(&self) -> &[TypeId] {
+        &self.types
+    }
+
+    pub fn entities(&self) -> &[EntityId] {
+        &self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn has(&self, ty: TypeId) -> bool {
+        self.columns.contains_key(&ty)
+    }
+
+    pub fn has_all(&self, types: &[TypeId]) -> bool {
+        types.iter().all(|ty| self.has(*ty))
+    }
+
+    pub fn column<T: 'static>(&self) -> Option<&Vec<T>> {
+        self.columns
+            .get(&TypeId::of::<T>())
+            .and_then(|cell| cell.get().as_any().downcast_ref::<Vec<T>>())
+    }
+
+    pub fn column_mut<T: 'static>(&mut self) -> Option<&mut Vec<T>> {
+        self.columns.get_mut(&TypeId::of::<T>()).and_then(|cell| {
+            cell.get_mut().as_any_mut().downcast_mut::<Vec<T>>()
+        })
+    }
+
+    /// Acquire a shared borrow on the column of `T`.
+    /// Panics if the column is already borrowed mutably.
+    pub fn borrow<T: 'static>(&self) {
+        if let Some(cell) = self.columns.get(&TypeId::of::<T>()) {
+            if !cell.borrow.borrow() {
+                panic!("{} already borrowed mutably.", type_name::<T>());
+            }
+        }
+    }
+
+    /// Acquire a unique borrow on the column of `T`.
+    /// Panics if the column is already borrowed.
+    pub fn borrow_mut<T: 'static>(&self) {
+        if let Some(cell) = self.columns.get(&TypeId::of::<T>()) {
+            if !cell.borrow.borrow_mut() {
+                panic!("{} already borrowed.", type_name::<T>());
+            }
+        }
+    }
+
+    pub fn release<T: 'static>(&self) {
+        if let Some(cell) = self.columns.get(&TypeId::of::<T>()) {
+            cell.borrow.release();
+        }
+    }
+
+    pub fn release_mut<T: 'static>(&self) {
+        if let Some(cell) = self.columns.get(&TypeId::of::<T>()) {
+            cell.borrow.release_mut();
+        }
+    }
+
+    /// Pointer to the first component of the column of `T`.
+    /// The column should be borrowed first.
+    pub fn get_ptr<T: 'static>(&self) -> Option<NonNull<T>> {
+        self.column::<T>().map(|column| unsafe {
+            NonNull::new_unchecked(column.as_ptr() as *mut T)
+        })
+    }
+
+    /// Mutable pointer to the first component of the column of `T`.
+    ///
+    /// # Safety
+    /// The column should be mutably borrowed first.
+    pub unsafe fn get_mut_ptr<T: 'static>(&self) -> Option<NonNull<T>> {
+        self.columns.get(&TypeId::of::<T>()).and_then(|cell| {
+            (*cell.data.get())
+                .as_any_mut()
+                .downcast_mut::<Vec<T>>()
+                .map(|column| NonNull::new_unchecked(column.as_mut_ptr()))
+        })
+    }
+
+    /// Change ticks of the component of `T` at the given row.
+    /// They are read through a pointer, as a query may hold mutable
+    /// references to other ticks of the column.
+    pub fn get_ticks<T: 'static>(&self, row: usize) -> Option<ComponentTicks> {
+        if row >= self.len() {
+            return None;
+        }
+
+        self.columns
+            .get(&TypeId::of::<T>())
+            .map(|cell| unsafe { (*cell.ticks.get()).as_ptr().add(row).read() })
+    }
+
+    /// Mutable pointer to the change ticks of the first component of the
+    /// column of `T`.
+    ///
+    /// # Safety
+    /// The column should be mutably borrowed first.
+    pub unsafe fn get_ticks_ptr<T: 'static>(
+        &self,
+    ) -> Option<NonNull<ComponentTicks>> {
+        self.columns.get(&TypeId::of::<T>()).map(|cell| {
+            NonNull::new_unchecked((*cell.ticks.get()).as_mut_ptr())
+        })
+    }
+
+    /// Push a new row, each given column should hold exactly one component,
+    /// with its change ticks.
+    /// Returns the row of the entity.
+    pub fn push(
+        &mut self,
+        id: EntityId,
+        mut components: Components,
+        ticks: Vec<ComponentTicks>,
+    ) -> usize {
+        components.iter_mut().zip(ticks).for_each(
+            |((ty, component), ticks)| {
+                let column = self
+                    .columns
+                    .get_mut(ty)
+                    .expect("Component type not found in the archetype.");
+
+                component.move_row(0, column.get_mut());
+                column.ticks.get_mut().push(ticks);
+            },
+        );
+
+        self.entities.push(id);
+        self.entities.len() - 1
+    }
+
+    /// Move the components of the entity at the given row out of the
+    /// archetype, each one in its own column, along with their ticks.
+    /// Also returns the entity moved into this row, if any.
+    pub fn take(
+        &mut self,
+        row: usize,
+    ) -> (Components, Vec<ComponentTicks>, Option<EntityId>) {
+        let Self {
+            types,
+            entities,
+            columns,
+        } = self;
+
+        let (components, ticks) = types
+            .iter()
+            .map(|ty| {
+                let cell = columns.get_mut(ty).expect("Column not found.");
+                let mut component = cell.get().new_empty();
+                cell.get_mut().move_row(row, component.as_mut());
+
+                ((*ty, component), cell.ticks.get_mut().swap_remove(row))
+            })
+            .unzip();
+
+        entities.swap_remove(row);
+        (components, ticks, entities.get(row).cloned())
+    }
+}
+
+impl fmt::Debug for Archetype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Archetype")
+            .field("types", &self.types)
+            .field("entities", &self.entities)
+            .finish()
+    }
+}
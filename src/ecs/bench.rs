@@ -0,0 +1,116 @@
+//! Compare the archetype storage with the previous storage, where each
+//! entity owned a `HashMap<TypeId, Box<Any>>` of components.
+//!
+//! Run it with:
+//! `cargo test --release bench_storage -- --ignored --nocapture`
+use super::{Entity, World};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::time::Instant;
+
+const ENTITIES: usize = 10_000;
+const FRAMES: usize = 100;
+
+#[derive(Debug)]
+struct Position(f32, f32, f32);
+#[derive(Debug)]
+struct Velocity(f32, f32, f32);
+#[derive(Debug)]
+struct Tag;
+
+/// The previous storage, kept here for comparison only.
+#[derive(Default)]
+struct LegacyEntity {
+    components: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl LegacyEntity {
+    fn with<T: 'static>(mut self, component: T) -> Self {
+        self.components
+            .insert(TypeId::of::<T>(), Box::new(component));
+        self
+    }
+
+    fn get_all_types(&self) -> Vec<&TypeId> {
+        self.components.keys().collect()
+    }
+
+    fn get<T: 'static>(&self) -> &T {
+        self.components[&TypeId::of::<T>()]
+            .downcast_ref::<T>()
+            .unwrap()
+    }
+
+    fn get_mut<T: 'static>(&mut self) -> &mut T {
+        self.components
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|any| any.downcast_mut::<T>())
+            .unwrap()
+    }
+}
+
+fn integrate(position: &mut Position, velocity: &Velocity) {
+    position.0 += velocity.0;
+    position.1 += velocity.1;
+    position.2 += velocity.2;
+}
+
+#[test]
+#[ignore]
+fn bench_storage() {
+    let targets = [TypeId::of::<Position>(), TypeId::of::<Velocity>()];
+
+    // Half of the entities don't match the targets.
+    let mut legacy: Vec<LegacyEntity> = (0..ENTITIES)
+        .map(|i| {
+            let entity = LegacyEntity::default().with(Position(0., 0., 0.));
+            if i % 2 == 0 {
+                entity.with(Velocity(1., 1., 1.))
+            } else {
+                entity.with(Tag)
+            }
+        })
+        .collect();
+
+    let mut world = World::new();
+    (0..ENTITIES).for_each(|i| {
+        let entity = Entity::new().with(Position(0., 0., 0.));
+        if i % 2 == 0 {
+            world.add_entity(entity.with(Velocity(1., 1., 1.)));
+        } else {
+            world.add_entity(entity.with(Tag));
+        }
+    });
+
+    let now = Instant::now();
+    for _ in 0..FRAMES {
+        legacy.iter_mut().for_each(|entity| {
+            let types = entity.get_all_types();
+            let matching = targets.iter().all(|target| types.contains(&target));
+
+            if matching {
+                let velocity = Velocity(
+                    entity.get::<Velocity>().0,
+                    entity.get::<Velocity>().1,
+                    entity.get::<Velocity>().2,
+                );
+                integrate(entity.get_mut::<Position>(), &velocity);
+            }
+        });
+    }
+    let legacy_time = now.elapsed();
+
+    let now = Instant::now();
+    for _ in 0..FRAMES {
+        let mut query = world.query::<(&mut Position, &Velocity)>();
+        query.iter().for_each(|(_, (mut position, velocity))| {
+            integrate(&mut position, velocity);
+        });
+    }
+    let archetype_time = now.elapsed();
+
+    println!(
+        "{} entities, {} frames\n  hashmap:   {:?}\n  archetype: {:?}",
+        ENTITIES, FRAMES, legacy_time, archetype_time
+    );
+}
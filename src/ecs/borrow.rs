@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const UNIQUE_BIT: usize = !(usize::MAX >> 1);
+
+/// A borrow flag, like the one inside a `RefCell`, but usable
+/// from multiple threads.
+#[derive(Default, Debug)]
+pub struct AtomicBorrow(AtomicUsize);
+
+impl AtomicBorrow {
+    /// Try to acquire a shared borrow. Fails if it is borrowed mutably.
+    pub fn borrow(&self) -> bool {
+        let previous = self.0.fetch_add(1, Ordering::Acquire);
+
+        if previous & UNIQUE_BIT != 0 {
+            self.0.fetch_sub(1, Ordering::Release);
+            return false;
+        }
+
+        true
+    }
+
+    /// Try to acquire a unique borrow. Fails if it is already borrowed.
+    pub fn borrow_mut(&self) -> bool {
+        self.0
+            .compare_exchange(
+                0,
+                UNIQUE_BIT,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    pub fn release(&self) {
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+
+    pub fn release_mut(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
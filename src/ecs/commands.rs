@@ -0,0 +1,124 @@
+use super::{Entity, EntityId, World};
+use std::fmt::{self, Debug};
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Buffer of structural changes requested by a system.
+///
+/// Systems only get a shared access to the world while running, so
+/// spawning, despawning or adding components is deferred: the buffer
+/// is applied by the world right after the system finished.
+#[derive(Default)]
+pub struct Commands {
+    queue: Vec<Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, entity: Entity) {
+        self.push(move |world| {
+            world.add_entity(entity);
+        });
+    }
+
+    /// Despawn the entity, releasing the resources owned by its
+    /// components (e.g. physic bodies).
+    pub fn despawn(&mut self, id: EntityId) {
+        self.push(move |world| {
+            world.despawn(id);
+        });
+    }
+
+    /// Add a component to the entity, replacing the previous one if any.
+    pub fn insert<T>(&mut self, id: EntityId, component: T)
+    where
+        T: Debug + Send + Sync + 'static,
+    {
+        self.push(move |world| {
+            world.insert(id, component);
+        });
+    }
+
+    pub fn remove<T: 'static>(&mut self, id: EntityId) {
+        self.push(move |world| {
+            world.remove::<T>(id);
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn push(&mut self, command: impl FnOnce(&mut World) + Send + 'static) {
+        self.queue.push(Box::new(command));
+    }
+
+    /// Apply all commands in the order they were pushed.
+    pub fn apply(self, world: &mut World) {
+        self.queue.into_iter().for_each(|command| command(world));
+    }
+}
+
+impl Debug for Commands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Commands")
+            .field("len", &self.queue.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::With;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    #[derive(Debug)]
+    struct Dead;
+
+    #[test]
+    fn should_defer_changes_until_applied() {
+        let mut world = World::new();
+        let alive = world.add_entity(Entity::new().with(Health(10)));
+        let dying = world.add_entity(Entity::new().with(Health(0)));
+
+        let mut commands = Commands::new();
+        {
+            let mut query = world.query::<&Health>();
+            for (id, health) in query.iter() {
+                if health.0 == 0 {
+                    commands.insert(id, Dead);
+                }
+            }
+        }
+        commands.spawn(Entity::new().with(Health(5)));
+        assert_eq!(commands.len(), 2);
+        assert_eq!(world.entities_len(), 2);
+
+        commands.apply(&mut world);
+        assert_eq!(world.entities_len(), 3);
+
+        let mut commands = Commands::new();
+        {
+            let mut query = world.query_filtered::<(), With<Dead>>();
+            for (id, _) in query.iter() {
+                commands.despawn(id);
+            }
+        }
+        commands.remove::<Health>(alive);
+        commands.apply(&mut world);
+
+        assert!(!world.contains(dying));
+        assert!(world.contains(alive));
+        assert_eq!(world.query::<&Health>().get(alive), None);
+        assert_eq!(world.query::<&Health>().iter().count(), 1);
+    }
+}
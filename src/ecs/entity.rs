@@ -0,0 +1,111 @@
+use super::archetype::{Column, Components};
+use std::any::TypeId;
+use std::fmt::{self, Debug};
+
+/// Stable id of an entity written in a scene file, entities of
+/// different scenes can share it.
+pub type SceneId = usize;
+
+/// Entity builder.
+/// Components are stored here until the entity is added to the `World`,
+/// then moved into the columns of the matching archetype.
+/// The runtime handle is given by the world when the entity is added.
+#[derive(Default)]
+pub struct Entity {
+    // Scene file the entity comes from, with its id in that file.
+    scene: Option<(String, SceneId)>,
+    components: Components,
+}
+
+impl Entity {
+    #[allow(unused)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entity described in a scene file.
+    /// Adding it twice to the same world replaces the first one.
+    pub fn from_file(scene: &str, id: SceneId) -> Self {
+        Self::new().with_scene_id(scene, id)
+    }
+
+    pub fn scene_id(&self) -> Option<SceneId> {
+        self.scene.as_ref().map(|(_, id)| *id)
+    }
+
+    /// Name of the scene file the entity comes from.
+    pub fn scene(&self) -> Option<&str> {
+        self.scene.as_ref().map(|(scene, _)| scene.as_str())
+    }
+
+    /// Mark the entity as described in a scene file, see `from_file`.
+    pub fn with_scene_id(mut self, scene: &str, id: SceneId) -> Self {
+        self.scene = Some((String::from(scene), id));
+        self
+    }
+
+    /// Component added to the builder, if any.
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.index_of::<T>().and_then(|index| {
+            let column = self.components[index].1.as_any();
+            column.downcast_ref::<Vec<T>>()?.first()
+        })
+    }
+
+    /// Remove a component from the builder, if it was added.
+    #[allow(unused)]
+    pub fn without<T: 'static>(mut self) -> Self {
+        if let Some(index) = self.index_of::<T>() {
+            self.components.remove(index);
+        }
+
+        self
+    }
+
+    pub fn with<T>(mut self, component: T) -> Self
+    where
+        T: Debug + Send + Sync + 'static,
+    {
+        let ty = TypeId::of::<T>();
+        let column: Box<dyn Column> = Box::new(vec![component]);
+
+        // Keep components sorted, the list of types is used as the key
+        // of the archetype.
+        match self.components.binary_search_by(|(t, _)| t.cmp(&ty)) {
+            Ok(index) => self.components[index].1 = column,
+            Err(index) => self.components.insert(index, (ty, column)),
+        }
+
+        self
+    }
+
+    /// Move a component out of the builder.
+    pub fn take<T: 'static>(&mut self) -> Option<T> {
+        let index = self.index_of::<T>()?;
+        let (_, mut column) = self.components.remove(index);
+
+        column.as_any_mut().downcast_mut::<Vec<T>>()?.pop()
+    }
+
+    fn index_of<T: 'static>(&self) -> Option<usize> {
+        let ty = TypeId::of::<T>();
+        self.components.binary_search_by(|(t, _)| t.cmp(&ty)).ok()
+    }
+
+    pub(super) fn get_all_types(&self) -> Vec<TypeId> {
+        self.components.iter().map(|(ty, _)| *ty).collect()
+    }
+
+    pub(super) fn into_components(self) -> Components {
+        self.components
+    }
+}
+
+impl Debug for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Entity")
+            .field("scene", &self.scene)
+            .field("types", &self.get_all_types())
+            .finish()
+    }
+}
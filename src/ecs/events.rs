@@ -0,0 +1,230 @@
+use super::{Access, Res, ResMut, SystemData, SystemTicks, World};
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::mem;
+
+/// Double-buffered queue of events of type `T`, stored as a resource.
+///
+/// Events are kept for two updates, so every reader sees them once,
+/// whether it runs before or after the writer in the frame.
+pub struct Events<T> {
+    // Events sent during the previous update, each one with its id.
+    previous: Vec<(usize, T)>,
+    current: Vec<(usize, T)>,
+    // Id of the next event.
+    count: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: vec![],
+            current: vec![],
+            count: 0,
+        }
+    }
+}
+
+impl<T> Events<T> {
+    pub fn send(&mut self, event: T) {
+        self.current.push((self.count, event));
+        self.count += 1;
+    }
+
+    /// Swap the buffers, events sent two updates ago are dropped.
+    /// Called by the world at the start of each frame.
+    pub fn update(&mut self) {
+        mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    /// Events the reader hasn't seen yet, the reader is moved after the
+    /// last one.
+    pub fn read<'e>(
+        &'e self,
+        reader: &mut ReaderId<T>,
+    ) -> impl Iterator<Item = &'e T> {
+        let start = reader.next;
+        reader.next = self.count;
+
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .filter(move |(id, _)| *id >= start)
+            .map(|(_, event)| event)
+    }
+
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Extend<T> for Events<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        events.into_iter().for_each(|event| self.send(event));
+    }
+}
+
+impl<T> Debug for Events<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Events")
+            .field("len", &self.len())
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+/// Cursor of one reader in an event queue, owned by the reading system.
+pub struct ReaderId<T> {
+    // Id of the next event to read.
+    next: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ReaderId<T> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for ReaderId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReaderId")
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+/// Send events of type `T` from a system.
+pub struct EventWriter<'a, T>(ResMut<'a, Events<T>>);
+
+impl<'a, T> EventWriter<'a, T> {
+    pub fn send(&mut self, event: T) {
+        self.0.send(event);
+    }
+}
+
+/// Read events of type `T` from a system, through its own `ReaderId`.
+pub struct EventReader<'a, T>(Res<'a, Events<T>>);
+
+impl<'a, T> EventReader<'a, T> {
+    pub fn read<'e>(
+        &'e self,
+        reader: &mut ReaderId<T>,
+    ) -> impl Iterator<Item = &'e T> {
+        self.0.read(reader)
+    }
+}
+
+impl<'a, T: Send + Sync + 'static> SystemData<'a> for EventWriter<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        EventWriter(world.resource_mut::<Events<T>>())
+    }
+
+    fn access(access: &mut Access) {
+        access.write_resource::<Events<T>>();
+    }
+}
+
+impl<'a, T: Send + Sync + 'static> SystemData<'a> for EventReader<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        EventReader(world.resource::<Events<T>>())
+    }
+
+    fn access(access: &mut Access) {
+        access.read_resource::<Events<T>>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::{Commands, System};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Hit(u32);
+
+    #[test]
+    fn should_read_each_event_once_per_reader() {
+        let mut events = Events::default();
+        let mut a = ReaderId::default();
+        let mut b = ReaderId::default();
+
+        events.send(Hit(1));
+        events.send(Hit(2));
+        assert_eq!(events.read(&mut a).collect::<Vec<_>>(), [&Hit(1), &Hit(2)]);
+
+        events.send(Hit(3));
+        assert_eq!(events.read(&mut a).collect::<Vec<_>>(), [&Hit(3)]);
+        assert_eq!(events.read(&mut a).count(), 0);
+        assert_eq!(events.read(&mut b).count(), 3);
+    }
+
+    #[test]
+    fn should_drop_events_after_two_updates() {
+        let mut events = Events::default();
+        let mut reader = ReaderId::default();
+
+        events.send(Hit(1));
+        events.update();
+        events.send(Hit(2));
+        assert_eq!(events.len(), 2);
+
+        events.update();
+        assert_eq!(events.read(&mut reader).collect::<Vec<_>>(), [&Hit(2)]);
+
+        events.update();
+        assert!(events.is_empty());
+    }
+
+    #[derive(Debug)]
+    struct Shoot;
+
+    impl<'a> System<'a> for Shoot {
+        type Data = EventWriter<'a, Hit>;
+
+        fn run(&mut self, mut hits: Self::Data, _: &mut Commands) {
+            hits.send(Hit(10));
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Total(usize);
+
+    #[derive(Debug, Default)]
+    struct Count {
+        reader: ReaderId<Hit>,
+    }
+
+    impl<'a> System<'a> for Count {
+        type Data = (EventReader<'a, Hit>, ResMut<'a, Total>);
+
+        fn run(&mut self, (hits, mut total): Self::Data, _: &mut Commands) {
+            total.0 += hits.read(&mut self.reader).count();
+        }
+    }
+
+    #[test]
+    fn should_publish_events_between_systems() {
+        let mut world = World::new();
+        world.add_event::<Hit>();
+        world.insert_resource(Total::default());
+
+        // The reader runs first, it sees the events on the next frame.
+        world.add_system(Count::default());
+        world.add_system(Shoot);
+        world.run(0.);
+        world.run(0.);
+        world.run(0.);
+
+        assert_eq!(world.resource::<Total>().0, 2);
+        assert_eq!(world.resource::<Events<Hit>>().len(), 2);
+    }
+}
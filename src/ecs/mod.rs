@@ -0,0 +1,29 @@
+mod allocator;
+mod archetype;
+#[cfg(test)]
+mod bench;
+mod borrow;
+mod commands;
+mod entity;
+mod events;
+mod query;
+mod reflect;
+mod resources;
+mod schedule;
+mod snapshot;
+mod system;
+mod ticks;
+mod world;
+
+pub use allocator::*;
+pub use commands::*;
+pub use entity::*;
+pub use events::*;
+pub use query::*;
+pub use reflect::*;
+pub use resources::*;
+pub use schedule::*;
+pub use snapshot::*;
+pub use system::*;
+pub use ticks::*;
+pub use world::*;
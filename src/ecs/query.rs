@@ -0,0 +1,586 @@
+use super::{
+    archetype::Archetype, Access, ComponentTicks, EntityId, Mut, SystemTicks,
+    World,
+};
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::slice;
+
+/// Anything which can be requested from a `Query`:
+/// `&T`, `&mut T`, `Option<&T>`, `Option<&mut T>` and tuples of those.
+/// `&mut T` is fetched as a `Mut<T>`, marking the component changed when
+/// it is written.
+pub trait WorldQuery {
+    type Fetch: for<'a> Fetch<'a>;
+}
+
+/// Fetch the components of a query from one archetype.
+pub trait Fetch<'a>: Sized {
+    type Item;
+
+    /// Whether the archetype holds all the required components.
+    fn matches(archetype: &Archetype) -> bool;
+
+    /// Acquire the borrows on the fetched columns.
+    /// Panics if a column is already borrowed in a conflicting way.
+    fn borrow(archetype: &Archetype);
+
+    fn release(archetype: &Archetype);
+
+    /// Declare the fetched components.
+    fn access(access: &mut Access);
+
+    /// # Safety
+    /// Columns must be borrowed first.
+    unsafe fn new(archetype: &'a Archetype, ticks: SystemTicks)
+        -> Option<Self>;
+
+    /// # Safety
+    /// `row` must be in bounds and returned only once for mutable fetches.
+    unsafe fn get(&self, row: usize) -> Self::Item;
+}
+
+/// Filters on the entities of a query: archetype-level, e.g. `With<T>`
+/// or `Without<T>`, or per entity on change ticks, e.g. `Changed<T>`.
+/// They don't borrow anything. Tick filters read the ticks through
+/// pointers, as the query may hand out mutable references to them, e.g.
+/// with `Query<&mut T, Changed<T>>`.
+pub trait QueryFilter {
+    fn matches(archetype: &Archetype) -> bool;
+
+    /// Whether the entity at the given row of a matching archetype
+    /// passes the filter.
+    fn matches_row(
+        _archetype: &Archetype,
+        _row: usize,
+        _ticks: SystemTicks,
+    ) -> bool {
+        true
+    }
+
+    /// Declare the components whose ticks are read.
+    fn access(_access: &mut Access) {}
+}
+
+pub struct FetchRead<T>(NonNull<T>);
+
+impl<T: 'static> WorldQuery for &T {
+    type Fetch = FetchRead<T>;
+}
+
+impl<'a, T: 'static> Fetch<'a> for FetchRead<T> {
+    type Item = &'a T;
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has(TypeId::of::<T>())
+    }
+
+    fn borrow(archetype: &Archetype) {
+        archetype.borrow::<T>();
+    }
+
+    fn release(archetype: &Archetype) {
+        archetype.release::<T>();
+    }
+
+    fn access(access: &mut Access) {
+        access.read_component::<T>();
+    }
+
+    unsafe fn new(archetype: &'a Archetype, _: SystemTicks) -> Option<Self> {
+        archetype.get_ptr::<T>().map(FetchRead)
+    }
+
+    unsafe fn get(&self, row: usize) -> Self::Item {
+        &*self.0.as_ptr().add(row)
+    }
+}
+
+pub struct FetchWrite<T> {
+    components: NonNull<T>,
+    ticks: NonNull<ComponentTicks>,
+    system_ticks: SystemTicks,
+}
+
+impl<T: 'static> WorldQuery for &mut T {
+    type Fetch = FetchWrite<T>;
+}
+
+impl<'a, T: 'static> Fetch<'a> for FetchWrite<T> {
+    type Item = Mut<'a, T>;
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has(TypeId::of::<T>())
+    }
+
+    fn borrow(archetype: &Archetype) {
+        archetype.borrow_mut::<T>();
+    }
+
+    fn release(archetype: &Archetype) {
+        archetype.release_mut::<T>();
+    }
+
+    fn access(access: &mut Access) {
+        access.write_component::<T>();
+    }
+
+    unsafe fn new(
+        archetype: &'a Archetype,
+        system_ticks: SystemTicks,
+    ) -> Option<Self> {
+        Some(FetchWrite {
+            components: archetype.get_mut_ptr::<T>()?,
+            ticks: archetype.get_ticks_ptr::<T>()?,
+            system_ticks,
+        })
+    }
+
+    unsafe fn get(&self, row: usize) -> Self::Item {
+        Mut {
+            value: &mut *self.components.as_ptr().add(row),
+            ticks: &mut *self.ticks.as_ptr().add(row),
+            system_ticks: self.system_ticks,
+        }
+    }
+}
+
+pub struct TryFetch<F>(Option<F>);
+
+impl<Q: WorldQuery> WorldQuery for Option<Q> {
+    type Fetch = TryFetch<Q::Fetch>;
+}
+
+impl<'a, F: Fetch<'a>> Fetch<'a> for TryFetch<F> {
+    type Item = Option<F::Item>;
+
+    fn matches(_archetype: &Archetype) -> bool {
+        true
+    }
+
+    fn borrow(archetype: &Archetype) {
+        if F::matches(archetype) {
+            F::borrow(archetype);
+        }
+    }
+
+    fn release(archetype: &Archetype) {
+        if F::matches(archetype) {
+            F::release(archetype);
+        }
+    }
+
+    fn access(access: &mut Access) {
+        F::access(access);
+    }
+
+    unsafe fn new(
+        archetype: &'a Archetype,
+        ticks: SystemTicks,
+    ) -> Option<Self> {
+        Some(TryFetch(F::new(archetype, ticks)))
+    }
+
+    unsafe fn get(&self, row: usize) -> Self::Item {
+        self.0.as_ref().map(|fetch| fetch.get(row))
+    }
+}
+
+/// Only match entities having a `T` component, without borrowing it.
+pub struct With<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for With<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has(TypeId::of::<T>())
+    }
+}
+
+/// Only match entities without a `T` component.
+pub struct Without<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for Without<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        !archetype.has(TypeId::of::<T>())
+    }
+}
+
+/// Only match entities whose `T` component was added since the last run
+/// of the system.
+pub struct Added<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for Added<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has(TypeId::of::<T>())
+    }
+
+    fn matches_row(
+        archetype: &Archetype,
+        row: usize,
+        ticks: SystemTicks,
+    ) -> bool {
+        match archetype.get_ticks::<T>(row) {
+            Some(component) => component.is_added(ticks),
+            None => false,
+        }
+    }
+
+    fn access(access: &mut Access) {
+        access.read_component::<T>();
+    }
+}
+
+/// Only match entities whose `T` component was added or changed since
+/// the last run of the system.
+pub struct Changed<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for Changed<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has(TypeId::of::<T>())
+    }
+
+    fn matches_row(
+        archetype: &Archetype,
+        row: usize,
+        ticks: SystemTicks,
+    ) -> bool {
+        match archetype.get_ticks::<T>(row) {
+            Some(component) => component.is_changed(ticks),
+            None => false,
+        }
+    }
+
+    fn access(access: &mut Access) {
+        access.read_component::<T>();
+    }
+}
+
+/// Match entities passing any of the filters of the tuple,
+/// e.g. `Or<(Changed<Transform>, Changed<Light>)>`.
+pub struct Or<T>(PhantomData<T>);
+
+macro_rules! tuple_impl {
+    ($($name: ident),*) => {
+        impl<'a, $($name: Fetch<'a>),*> Fetch<'a> for ($($name,)*) {
+            type Item = ($($name::Item,)*);
+
+            fn matches(_archetype: &Archetype) -> bool {
+                $($name::matches(_archetype) &&)* true
+            }
+
+            fn borrow(_archetype: &Archetype) {
+                $($name::borrow(_archetype);)*
+            }
+
+            fn release(_archetype: &Archetype) {
+                $($name::release(_archetype);)*
+            }
+
+            fn access(_access: &mut Access) {
+                $($name::access(_access);)*
+            }
+
+            unsafe fn new(
+                _archetype: &'a Archetype,
+                _ticks: SystemTicks,
+            ) -> Option<Self> {
+                Some(($($name::new(_archetype, _ticks)?,)*))
+            }
+
+            #[allow(non_snake_case, clippy::unused_unit)]
+            unsafe fn get(&self, _row: usize) -> Self::Item {
+                let ($($name,)*) = self;
+                ($($name.get(_row),)*)
+            }
+        }
+
+        impl<$($name: WorldQuery),*> WorldQuery for ($($name,)*) {
+            type Fetch = ($($name::Fetch,)*);
+        }
+
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            fn matches(_archetype: &Archetype) -> bool {
+                $($name::matches(_archetype) &&)* true
+            }
+
+            fn matches_row(
+                _archetype: &Archetype,
+                _row: usize,
+                _ticks: SystemTicks,
+            ) -> bool {
+                $($name::matches_row(_archetype, _row, _ticks) &&)* true
+            }
+
+            fn access(_access: &mut Access) {
+                $($name::access(_access);)*
+            }
+        }
+
+        impl<$($name: QueryFilter),*> QueryFilter for Or<($($name,)*)> {
+            fn matches(_archetype: &Archetype) -> bool {
+                $($name::matches(_archetype) ||)* false
+            }
+
+            // Only the filters matching the archetype are checked.
+            fn matches_row(
+                _archetype: &Archetype,
+                _row: usize,
+                _ticks: SystemTicks,
+            ) -> bool {
+                $(($name::matches(_archetype)
+                    && $name::matches_row(_archetype, _row, _ticks)) ||)*
+                    false
+            }
+
+            fn access(_access: &mut Access) {
+                $($name::access(_access);)*
+            }
+        }
+    };
+}
+
+tuple_impl!();
+tuple_impl!(A);
+tuple_impl!(A, B);
+tuple_impl!(A, B, C);
+tuple_impl!(A, B, C, D);
+tuple_impl!(A, B, C, D, E);
+tuple_impl!(A, B, C, D, E, F);
+tuple_impl!(A, B, C, D, E, F, G);
+tuple_impl!(A, B, C, D, E, F, G, H);
+
+/// Borrow all components matching `Q` and the filter `F`.
+///
+/// Borrows are checked at runtime when the query is created:
+/// e.g. `(&mut Transform, &Transform)` or two living queries writing the
+/// same component will panic.
+///
+/// ```ignore
+/// let mut query = world.query::<(&mut Transform, Option<&Light>)>();
+/// for (id, (transform, light)) in query.iter() {}
+/// ```
+pub struct Query<'w, Q: WorldQuery, F: QueryFilter = ()> {
+    world: &'w World,
+    ticks: SystemTicks,
+    _marker: PhantomData<(Q, F)>,
+}
+
+impl<'w, Q: WorldQuery, F: QueryFilter> Query<'w, Q, F> {
+    pub(super) fn new(world: &'w World, ticks: SystemTicks) -> Self {
+        world
+            .archetypes()
+            .iter()
+            .filter(|archetype| Self::matches(archetype))
+            .for_each(Q::Fetch::borrow);
+
+        Self {
+            world,
+            ticks,
+            _marker: PhantomData,
+        }
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        Q::Fetch::matches(archetype) && F::matches(archetype)
+    }
+
+    pub fn iter(&mut self) -> QueryIter<'_, Q, F> {
+        QueryIter {
+            archetypes: self.world.archetypes().iter(),
+            current: None,
+            row: 0,
+            ticks: self.ticks,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fetch the components of one entity, if it matches the query.
+    pub fn get(
+        &mut self,
+        id: EntityId,
+    ) -> Option<<Q::Fetch as Fetch<'_>>::Item> {
+        let (archetype, row) = self.world.location(id)?;
+        let archetype = &self.world.archetypes()[archetype];
+
+        if !Self::matches(archetype)
+            || !F::matches_row(archetype, row, self.ticks)
+        {
+            return None;
+        }
+
+        unsafe {
+            Q::Fetch::new(archetype, self.ticks).map(|fetch| fetch.get(row))
+        }
+    }
+}
+
+impl<'w, Q: WorldQuery, F: QueryFilter> Drop for Query<'w, Q, F> {
+    fn drop(&mut self) {
+        self.world
+            .archetypes()
+            .iter()
+            .filter(|archetype| Self::matches(archetype))
+            .for_each(Q::Fetch::release);
+    }
+}
+
+pub struct QueryIter<'q, Q: WorldQuery, F: QueryFilter> {
+    archetypes: slice::Iter<'q, Archetype>,
+    current: Option<(&'q Archetype, Q::Fetch)>,
+    row: usize,
+    ticks: SystemTicks,
+    _marker: PhantomData<F>,
+}
+
+impl<'q, Q: WorldQuery, F: QueryFilter> Iterator for QueryIter<'q, Q, F> {
+    type Item = (EntityId, <Q::Fetch as Fetch<'q>>::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((archetype, fetch)) = self.current.as_ref() {
+                while self.row < archetype.len() {
+                    let row = self.row;
+                    self.row += 1;
+
+                    if !F::matches_row(archetype, row, self.ticks) {
+                        continue;
+                    }
+
+                    let id = archetype.entities()[row];
+                    return Some((id, unsafe { fetch.get(row) }));
+                }
+            }
+
+            let archetype = self.archetypes.next()?;
+            self.row = 0;
+            self.current = None;
+
+            if Query::<Q, F>::matches(archetype) {
+                self.current = unsafe { Q::Fetch::new(archetype, self.ticks) }
+                    .map(|fetch| (archetype, fetch));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::Entity;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug, PartialEq)]
+    struct Velocity(f32);
+    #[derive(Debug)]
+    struct Frozen;
+
+    fn world() -> World {
+        let mut world = World::new();
+
+        world.add_entity(Entity::new().with(Position(0.)).with(Velocity(1.)));
+        world.add_entity(
+            Entity::new()
+                .with(Position(0.))
+                .with(Velocity(2.))
+                .with(Frozen),
+        );
+        world.add_entity(Entity::new().with(Position(5.)));
+        world
+    }
+
+    #[test]
+    fn should_borrow_multiple_components_mutably() {
+        let world = world();
+
+        {
+            let mut query = world.query::<(&mut Position, &mut Velocity)>();
+            for (_, (mut position, mut velocity)) in query.iter() {
+                position.0 += velocity.0;
+                velocity.0 = 0.;
+            }
+        }
+
+        let mut query = world.query::<(&Position, &Velocity)>();
+        let mut positions: Vec<f32> =
+            query.iter().map(|(_, (p, v))| p.0 + v.0).collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(positions, vec![1., 2.]);
+    }
+
+    #[test]
+    fn should_fetch_optional_components() {
+        let world = world();
+
+        let mut query = world.query::<(&Position, Option<&Velocity>)>();
+        let (with, without): (Vec<_>, Vec<_>) = query
+            .iter()
+            .partition(|(_, (_, velocity))| velocity.is_some());
+
+        assert_eq!(with.len(), 2);
+        assert_eq!(without.len(), 1);
+        assert_eq!((without[0].1).0, &Position(5.));
+    }
+
+    #[test]
+    fn should_filter_with_and_without() {
+        let world = world();
+
+        let mut query = world.query_filtered::<&Velocity, With<Frozen>>();
+        let frozen: Vec<_> = query.iter().map(|(_, v)| v.0).collect();
+        assert_eq!(frozen, vec![2.]);
+
+        let mut query = world
+            .query_filtered::<&Position, (With<Velocity>, Without<Frozen>)>();
+        assert_eq!(query.iter().count(), 1);
+    }
+
+    #[test]
+    fn should_get_one_entity() {
+        let mut world = World::new();
+        let id = world.add_entity(Entity::new().with(Position(3.)));
+
+        let mut query = world.query::<&mut Position>();
+        query.get(id).unwrap().0 = 4.;
+        assert_eq!(query.get(id).as_deref(), Some(&Position(4.)));
+
+        let mut query = world.query::<&Velocity>();
+        assert!(query.get(id).is_none());
+    }
+
+    #[test]
+    fn should_share_immutable_borrows() {
+        let world = world();
+
+        let _a = world.query::<&Position>();
+        let _b = world.query::<(&Position, &Velocity)>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_panic_on_conflicting_borrows_in_one_query() {
+        let world = world();
+        let _ = world.query::<(&mut Position, &Position)>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_panic_on_conflicting_queries() {
+        let world = world();
+
+        let _a = world.query::<&mut Position>();
+        let _b = world.query::<&Position>();
+    }
+
+    #[test]
+    fn should_release_borrows_on_drop() {
+        let world = world();
+
+        {
+            let _ = world.query::<&mut Position>();
+        }
+        let mut query = world.query::<&mut Position>();
+        assert_eq!(query.iter().count(), 3);
+    }
+}
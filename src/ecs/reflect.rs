@@ -0,0 +1,465 @@
+use super::{Commands, Entity, EntityId, World};
+use serde::de::{self, DeserializeOwned, Deserializer, Error, Visitor};
+use serde::{forward_to_deserialize_any, Serialize, Serializer};
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+
+type DeserializeFn = for<'de> fn(
+    &mut dyn erased_serde::Deserializer<'de>,
+    Entity,
+) -> Result<Entity, erased_serde::Error>;
+type SerializeFn =
+    fn(&World, EntityId, &mut dyn FnMut(&dyn erased_serde::Serialize)) -> bool;
+type PatchFn = fn(&World, EntityId, &mut Entity, &mut Commands) -> bool;
+
+/// What the world knows about a registered component type: its name in
+/// files, its fields and how to read, write or build it without knowing
+/// its type.
+#[derive(Clone)]
+pub struct ComponentRegistration {
+    name: &'static str,
+    type_id: TypeId,
+    type_name: &'static str,
+    fields: &'static [&'static str],
+    deserialize: DeserializeFn,
+    serialize: SerializeFn,
+    patch: PatchFn,
+    default: Option<fn(Entity) -> Entity>,
+}
+
+impl ComponentRegistration {
+    fn new<T>() -> Self
+    where
+        T: Serialize
+            + DeserializeOwned
+            + PartialEq
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+    {
+        let mut introspect = Introspect::default();
+        let _ = T::deserialize(&mut introspect);
+
+        // Name of the type without its path, if serde doesn't give one.
+        let name = introspect.name.unwrap_or_else(|| {
+            let name = type_name::<T>();
+            name.rsplit("::").next().unwrap_or(name)
+        });
+
+        Self {
+            name,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            fields: introspect.fields,
+            deserialize: |deserializer, entity| {
+                let component: T = erased_serde::deserialize(deserializer)?;
+                Ok(entity.with(component))
+            },
+            serialize: |world, id, serialize| {
+                world
+                    .query::<&T>()
+                    .get(id)
+                    .map(|component| serialize(component))
+                    .is_some()
+            },
+            patch: |world, id, entity, commands| match entity.take::<T>() {
+                Some(component) => match world.query::<&mut T>().get(id) {
+                    Some(current) if *current == component => false,
+                    Some(mut current) => {
+                        *current = component;
+                        true
+                    }
+                    None => {
+                        commands.insert(id, component);
+                        true
+                    }
+                },
+                None => {
+                    let removed = world.query::<&T>().get(id).is_some();
+                    if removed {
+                        commands.remove::<T>(id);
+                    }
+                    removed
+                }
+            },
+            default: None,
+        }
+    }
+
+    /// Name of the component in files.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[allow(unused)]
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Named fields of the component, empty for tuple and unit structs.
+    pub fn fields(&self) -> &'static [&'static str] {
+        self.fields
+    }
+
+    /// Whether the entity has this component.
+    pub fn contains(&self, world: &World, id: EntityId) -> bool {
+        (self.serialize)(world, id, &mut |_| {})
+    }
+
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+
+    /// Read the component and add it to the entity builder.
+    pub fn deserialize<'de, D>(
+        &self,
+        deserializer: D,
+        entity: Entity,
+    ) -> Result<Entity, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut deserializer =
+            <dyn erased_serde::Deserializer>::erase(deserializer);
+
+        (self.deserialize)(&mut deserializer, entity).map_err(D::Error::custom)
+    }
+
+    /// Write the component of the entity, `None` if it doesn't have one.
+    pub fn serialize<S>(
+        &self,
+        world: &World,
+        id: EntityId,
+        serializer: S,
+    ) -> Option<Result<S::Ok, S::Error>>
+    where
+        S: Serializer,
+    {
+        let mut serializer = Some(serializer);
+        let mut result = None;
+
+        (self.serialize)(world, id, &mut |component| {
+            if let Some(serializer) = serializer.take() {
+                result = Some(erased_serde::serialize(component, serializer));
+            }
+        });
+
+        result
+    }
+
+    /// Add a default component to the entity builder, `None` if the type
+    /// has been registered without a default constructor.
+    #[allow(unused)]
+    pub fn default(&self, entity: Entity) -> Option<Entity> {
+        self.default.map(|default| default(entity))
+    }
+}
+
+impl Debug for ComponentRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ComponentRegistration")
+            .field("name", &self.name)
+            .field("type", &self.type_name)
+            .field("fields", &self.fields)
+            .field("default", &self.has_default())
+            .finish()
+    }
+}
+
+/// Component types which can be read from and written to files by name.
+/// The scene loader, the editor or save games go through it instead of
+/// matching on every known component.
+#[derive(Debug, Default, Clone)]
+pub struct ComponentRegistry {
+    registrations: Vec<ComponentRegistration>,
+    names: HashMap<&'static str, usize>,
+    types: HashMap<TypeId, usize>,
+}
+
+impl ComponentRegistry {
+    pub fn register<T>(&mut self) -> &mut ComponentRegistration
+    where
+        T: Serialize
+            + DeserializeOwned
+            + PartialEq
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+    {
+        let registration = ComponentRegistration::new::<T>();
+
+        let index = match self.types.get(&registration.type_id) {
+            Some(&index) => {
+                self.names.remove(self.registrations[index].name);
+                self.registrations[index] = registration;
+                index
+            }
+            None => {
+                self.registrations.push(registration);
+                self.registrations.len() - 1
+            }
+        };
+
+        let registration = &mut self.registrations[index];
+        self.names.insert(registration.name, index);
+        self.types.insert(registration.type_id, index);
+
+        registration
+    }
+
+    /// Register a component which can also be built without any data.
+    pub fn register_default<T>(&mut self) -> &mut ComponentRegistration
+    where
+        T: Serialize
+            + DeserializeOwned
+            + PartialEq
+            + Default
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+    {
+        let registration = self.register::<T>();
+        registration.default = Some(|entity| entity.with(T::default()));
+        registration
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.names
+            .get(name)
+            .map(|&index| &self.registrations[index])
+    }
+
+    #[allow(unused)]
+    pub fn get_type<T: 'static>(&self) -> Option<&ComponentRegistration> {
+        self.types
+            .get(&TypeId::of::<T>())
+            .map(|&index| &self.registrations[index])
+    }
+
+    /// Make the registered components of the living entity `id` equal to
+    /// the ones of the builder, moving them out of it.
+    /// Components are written in place when they differ, the ones to add
+    /// or remove go through `commands`.
+    /// Returns the types which changed.
+    pub fn patch(
+        &self,
+        world: &World,
+        id: EntityId,
+        entity: &mut Entity,
+        commands: &mut Commands,
+    ) -> Vec<TypeId> {
+        self.registrations
+            .iter()
+            .filter(|registration| {
+                (registration.patch)(world, id, entity, commands)
+            })
+            .map(|registration| registration.type_id)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentRegistration> {
+        self.registrations.iter()
+    }
+}
+
+/// Deserializer which only records the name and fields a `Deserialize`
+/// impl asks for, then gives up.
+#[derive(Default)]
+struct Introspect {
+    name: Option<&'static str>,
+    fields: &'static [&'static str],
+}
+
+impl<'de> Deserializer<'de> for &mut Introspect {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::custom("introspection"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.name = Some(name);
+        self.fields = fields;
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.name = Some(name);
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.name = Some(name);
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.name = Some(name);
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.name = Some(name);
+        self.deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit seq tuple map identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Frozen;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Velocity(f32);
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::default();
+        registry.register::<Position>();
+        registry.register_default::<Frozen>();
+        registry
+    }
+
+    #[test]
+    fn should_describe_registered_components() {
+        let registry = registry();
+
+        let position = registry.get("Position").unwrap();
+        assert_eq!(position.fields(), ["x", "y"]);
+        assert!(!position.has_default());
+        assert_eq!(position.type_id(), TypeId::of::<Position>());
+
+        let frozen = registry.get_type::<Frozen>().unwrap();
+        assert_eq!(frozen.name(), "Frozen");
+        assert!(frozen.fields().is_empty());
+        assert!(registry.get("Velocity").is_none());
+    }
+
+    #[test]
+    fn should_read_and_write_components_by_name() {
+        let registry = registry();
+        let mut world = World::new();
+
+        let position = registry.get("Position").unwrap();
+        let mut deserializer =
+            ron::de::Deserializer::from_str("(x: 1., y: 2.)");
+        let entity = position.deserialize(&mut deserializer, Entity::new());
+        let entity = registry.get("Frozen").unwrap().default(entity.unwrap());
+        let id = world.add_entity(entity.unwrap());
+
+        {
+            let mut query = world.query::<(&Position, &Frozen)>();
+            assert_eq!(query.get(id).unwrap().0, &Position { x: 1., y: 2. });
+        }
+
+        let mut output = ron::ser::Serializer::new(None, false);
+        position
+            .serialize(&world, id, &mut output)
+            .unwrap()
+            .unwrap();
+        assert_eq!(output.into_output_string(), "(x:1,y:2,)");
+
+        let empty = world.add_entity(Entity::new());
+        assert!(position
+            .serialize(
+                &world,
+                empty,
+                &mut ron::ser::Serializer::new(None, false)
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn should_patch_changed_components() {
+        let mut registry = registry();
+        registry.register::<Velocity>();
+        let mut world = World::new();
+        let id = world.add_entity(
+            Entity::new()
+                .with(Position { x: 1., y: 2. })
+                .with(Velocity(1.))
+                .with(Frozen),
+        );
+
+        let mut entity = Entity::new()
+            .with(Position { x: 1., y: 2. })
+            .with(Velocity(2.))
+            .with(Frozen)
+            .with("not registered");
+        let mut commands = Commands::new();
+        let changed = registry.patch(&world, id, &mut entity, &mut commands);
+        commands.apply(&mut world);
+
+        assert_eq!(changed, [TypeId::of::<Velocity>()]);
+        assert_eq!(world.query::<&Velocity>().get(id), Some(&Velocity(2.)));
+        // Unregistered components are left in the builder.
+        assert_eq!(entity.get::<&str>(), Some(&"not registered"));
+
+        let mut entity = Entity::new().with(Velocity(2.));
+        let mut commands = Commands::new();
+        let changed = registry.patch(&world, id, &mut entity, &mut commands);
+        commands.apply(&mut world);
+
+        assert_eq!(changed, [TypeId::of::<Position>(), TypeId::of::<Frozen>()]);
+        assert!(world.query::<&Position>().get(id).is_none());
+        assert!(world.query::<&Frozen>().get(id).is_none());
+    }
+}
@@ -0,0 +1,265 @@
+use super::borrow::AtomicBorrow;
+use std::any::{type_name, Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::thread::{self, ThreadId};
+
+struct ResourceCell {
+    borrow: AtomicBorrow,
+    // Only reachable from the main thread.
+    non_send: bool,
+    data: UnsafeCell<Box<dyn Any>>,
+}
+
+/// Global data shared by systems (e.g. the window, the physic world),
+/// stored by type.
+pub struct Resources {
+    data: HashMap<TypeId, ResourceCell>,
+    main_thread: ThreadId,
+}
+
+impl Default for Resources {
+    fn default() -> Self {
+        Self {
+            data: HashMap::new(),
+            main_thread: thread::current().id(),
+        }
+    }
+}
+
+impl Resources {
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.insert_cell(value, false);
+    }
+
+    /// Insert a resource which can't leave the main thread, the thread
+    /// which created the resources (e.g. anything using the GL context).
+    pub fn insert_non_send<T: 'static>(&mut self, value: T) {
+        self.insert_cell(value, true);
+    }
+
+    fn insert_cell<T: 'static>(&mut self, value: T, non_send: bool) {
+        let cell = ResourceCell {
+            borrow: AtomicBorrow::default(),
+            non_send,
+            data: UnsafeCell::new(Box::new(value)),
+        };
+
+        self.data.insert(TypeId::of::<T>(), cell);
+    }
+
+    /// Panics if the resource is not send and this is not the main thread.
+    fn cell<T: 'static>(&self) -> Option<&ResourceCell> {
+        let cell = self.data.get(&TypeId::of::<T>())?;
+
+        if cell.non_send && thread::current().id() != self.main_thread {
+            panic!(
+                "{} can only be accessed from the main thread.",
+                type_name::<T>()
+            );
+        }
+
+        Some(cell)
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.data
+            .remove(&TypeId::of::<T>())
+            .and_then(|cell| cell.data.into_inner().downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.data.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Whether the resource was inserted with `insert_non_send`.
+    pub fn is_non_send(&self, ty: TypeId) -> bool {
+        matches!(self.data.get(&ty), Some(cell) if cell.non_send)
+    }
+
+    /// Panics if the resource is already borrowed mutably.
+    pub fn get<T: 'static>(&self) -> Option<Res<'_, T>> {
+        self.cell::<T>().map(|cell| {
+            if !cell.borrow.borrow() {
+                panic!("{} already borrowed mutably.", type_name::<T>());
+            }
+
+            let value = unsafe { (*cell.data.get()).downcast_ref::<T>() };
+
+            Res {
+                value: value.expect("Resource stored with a wrong type."),
+                borrow: &cell.borrow,
+            }
+        })
+    }
+
+    /// Panics if the resource is already borrowed.
+    pub fn get_mut<T: 'static>(&self) -> Option<ResMut<'_, T>> {
+        self.cell::<T>().map(|cell| {
+            if !cell.borrow.borrow_mut() {
+                panic!("{} already borrowed.", type_name::<T>());
+            }
+
+            let value = unsafe { (*cell.data.get()).downcast_mut::<T>() };
+
+            ResMut {
+                value: value.expect("Resource stored with a wrong type."),
+                borrow: &cell.borrow,
+            }
+        })
+    }
+}
+
+// Resources are inserted as `Send + Sync` unless marked as non send, those
+// ones can't be reached outside of the main thread.
+unsafe impl Sync for Resources {}
+
+impl fmt::Debug for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Resources")
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// Shared borrow of a resource, released when dropped.
+pub struct Res<'a, T> {
+    value: &'a T,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T> Deref for Res<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T> Drop for Res<'a, T> {
+    fn drop(&mut self) {
+        self.borrow.release();
+    }
+}
+
+/// Unique borrow of a resource, released when dropped.
+pub struct ResMut<'a, T> {
+    value: &'a mut T,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T> Deref for ResMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for ResMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'a, T> Drop for ResMut<'a, T> {
+    fn drop(&mut self) {
+        self.borrow.release_mut();
+    }
+}
+
+/// Shared borrow of a resource which isn't send, systems fetching it
+/// run on the main thread.
+pub struct NonSend<'a, T>(Res<'a, T>);
+
+impl<'a, T> NonSend<'a, T> {
+    pub(super) fn new(res: Res<'a, T>) -> Self {
+        NonSend(res)
+    }
+}
+
+impl<'a, T> Deref for NonSend<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Unique borrow of a resource which isn't send, systems fetching it
+/// run on the main thread.
+pub struct NonSendMut<'a, T>(ResMut<'a, T>);
+
+impl<'a, T> NonSendMut<'a, T> {
+    pub(super) fn new(res: ResMut<'a, T>) -> Self {
+        NonSendMut(res)
+    }
+}
+
+impl<'a, T> Deref for NonSendMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'a, T> DerefMut for NonSendMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Gravity(f32);
+
+    #[test]
+    fn should_insert_and_borrow_resources() {
+        let mut resources = Resources::default();
+        resources.insert(Gravity(-9.8));
+        assert!(resources.contains::<Gravity>());
+
+        {
+            let a = resources.get::<Gravity>().unwrap();
+            let b = resources.get::<Gravity>().unwrap();
+            assert_eq!(*a, *b);
+        }
+
+        resources.get_mut::<Gravity>().unwrap().0 = 0.;
+        assert_eq!(*resources.get::<Gravity>().unwrap(), Gravity(0.));
+        assert_eq!(resources.remove::<Gravity>(), Some(Gravity(0.)));
+        assert!(resources.get::<Gravity>().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_panic_on_conflicting_borrows() {
+        let mut resources = Resources::default();
+        resources.insert(Gravity(-9.8));
+
+        let _a = resources.get::<Gravity>();
+        let _b = resources.get_mut::<Gravity>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_keep_non_send_resources_on_main_thread() {
+        let mut resources = Resources::default();
+        resources.insert_non_send(Gravity(-9.8));
+        assert!(resources.get::<Gravity>().is_some());
+
+        let resources = &resources;
+        rayon::scope(|scope| {
+            scope.spawn(move |_| {
+                resources.get::<Gravity>();
+            })
+        });
+    }
+}
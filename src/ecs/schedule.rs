@@ -0,0 +1,643 @@
+use super::{Access, Commands, RunSystem, SystemTicks, World};
+use std::any::type_name;
+use std::fmt::{self, Debug, Display};
+use std::sync::Mutex;
+
+/// Stages run one after the other, in this order, every frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    PreUpdate,
+    /// Run zero or more times per frame, at a fixed timestep.
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    Render,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::PreUpdate,
+        Stage::FixedUpdate,
+        Stage::Update,
+        Stage::PostUpdate,
+        Stage::Render,
+    ];
+}
+
+/// How the systems of a stage are run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Executor {
+    /// One after the other on the calling thread, in a deterministic
+    /// order.
+    SingleThreaded,
+    /// Systems which don't conflict run at the same time on the thread
+    /// pool, systems pinned to the main thread run on the calling thread.
+    Parallel,
+}
+
+// Avoid running the fixed stage forever when a frame took too long.
+const MAX_FIXED_STEPS: u32 = 8;
+
+type RunCondition = Box<dyn Fn(&World) -> bool>;
+
+struct SystemEntry {
+    system: Box<dyn RunSystem>,
+    access: Access,
+    stage: Stage,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    run_if: Vec<RunCondition>,
+    // World tick of the previous run.
+    last_run: u32,
+}
+
+impl SystemEntry {
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+
+    fn should_run(&self, world: &World) -> bool {
+        self.run_if.iter().all(|condition| condition(world))
+    }
+
+    fn is_constrained_with(&self, other: &SystemEntry) -> bool {
+        self.before.contains(&other.name())
+            || self.after.contains(&other.name())
+    }
+
+    /// Whether both systems can be part of the same batch.
+    fn can_run_with(&self, other: &SystemEntry) -> bool {
+        self.access.is_compatible(&other.access)
+            && !self.is_constrained_with(other)
+            && !other.is_constrained_with(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleError {
+    /// Systems of a stage whose `before`/`after` constraints form a cycle.
+    Cycle(Stage, Vec<&'static str>),
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScheduleError::Cycle(stage, systems) => write!(
+                f,
+                "Ordering cycle in {:?} between: {}.",
+                stage,
+                systems.join(", ")
+            ),
+        }
+    }
+}
+
+/// Run a system with the ticks telling what changed since its last run.
+fn run_system(
+    system: &mut Box<dyn RunSystem>,
+    last_run: &mut u32,
+    world: &World,
+    commands: &mut Commands,
+) {
+    let ticks = SystemTicks {
+        last_run: *last_run,
+        this_run: world.increment_change_tick(),
+    };
+
+    system.run_now(world, ticks, commands);
+    *last_run = ticks.this_run;
+}
+
+/// Run the systems of a world, stage by stage.
+///
+/// Inside a stage, systems run in insertion order unless constrained with
+/// `before`/`after`.
+/// Consecutive systems with compatible accesses are grouped in batches,
+/// the systems of a batch can run in parallel.
+pub struct Schedule {
+    systems: Vec<SystemEntry>,
+    // Batches of system indices of each stage, `None` once a system is
+    // added.
+    batches: Option<Vec<Vec<Vec<usize>>>>,
+    executor: Executor,
+    fixed_timestep: f64,
+    accumulator: f64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            systems: vec![],
+            batches: None,
+            executor: Executor::Parallel,
+            fixed_timestep: 1. / 60.,
+            accumulator: 0.,
+        }
+    }
+}
+
+impl Schedule {
+    pub fn add_system(
+        &mut self,
+        stage: Stage,
+        system: Box<dyn RunSystem>,
+    ) -> SystemConfig<'_> {
+        self.batches = None;
+        self.systems.push(SystemEntry {
+            access: system.access(),
+            system,
+            stage,
+            before: vec![],
+            after: vec![],
+            run_if: vec![],
+            last_run: 0,
+        });
+
+        SystemConfig {
+            entry: self.systems.last_mut().unwrap(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn set_executor(&mut self, executor: Executor) {
+        self.executor = executor;
+    }
+
+    pub fn fixed_timestep(&self) -> f64 {
+        self.fixed_timestep
+    }
+
+    pub fn set_fixed_timestep(&mut self, step: f64) {
+        self.fixed_timestep = step;
+    }
+
+    /// Sort the systems of each stage, done on the first run otherwise.
+    pub fn build(&mut self) -> Result<(), ScheduleError> {
+        if self.batches.is_some() {
+            return Ok(());
+        }
+
+        let batches = Stage::ALL
+            .iter()
+            .map(|stage| Ok(self.batch(self.sort_stage(*stage)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.batches = Some(batches);
+        Ok(())
+    }
+
+    /// Run all stages once, `dt` feeds the fixed stage.
+    /// Panics if the systems can't be ordered.
+    pub fn run(&mut self, world: &mut World, dt: f64) {
+        if let Err(error) = self.build() {
+            panic!("{}", error);
+        }
+
+        self.accumulator += dt;
+
+        for stage in Stage::ALL.iter() {
+            if *stage != Stage::FixedUpdate {
+                self.run_stage(*stage, world);
+                continue;
+            }
+
+            let mut steps = 0;
+            while self.accumulator >= self.fixed_timestep {
+                self.accumulator -= self.fixed_timestep;
+
+                if steps < MAX_FIXED_STEPS {
+                    self.run_stage(*stage, world);
+                    steps += 1;
+                }
+            }
+        }
+    }
+
+    fn run_stage(&mut self, stage: Stage, world: &mut World) {
+        let Self {
+            systems,
+            batches,
+            executor,
+            ..
+        } = self;
+
+        for batch in batches.as_ref().unwrap()[stage as usize].iter() {
+            // Conditions are checked on the calling thread, before the
+            // batch starts.
+            let batch: Vec<usize> = batch
+                .iter()
+                .cloned()
+                .filter(|index| systems[*index].should_run(world))
+                .collect();
+
+            if *executor == Executor::Parallel && batch.len() > 1 {
+                Self::run_parallel(systems, &batch, world);
+                continue;
+            }
+
+            for index in batch {
+                let mut commands = Commands::new();
+                let entry = &mut systems[index];
+                run_system(
+                    &mut entry.system,
+                    &mut entry.last_run,
+                    world,
+                    &mut commands,
+                );
+
+                // Sync point.
+                commands.apply(world);
+                world.release_removed();
+            }
+        }
+    }
+
+    fn run_parallel(
+        systems: &mut [SystemEntry],
+        batch: &[usize],
+        world: &mut World,
+    ) {
+        let results = Mutex::new(vec![]);
+
+        {
+            let world = &*world;
+            let results = &results;
+            let (pinned, pooled): (Vec<_>, Vec<_>) = systems
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| batch.contains(index))
+                .map(|(index, entry)| {
+                    // Non send resources fetched with `Res` or `ResMut`
+                    // pin the system too.
+                    let main_thread = entry.access.is_main_thread()
+                        || entry
+                            .access
+                            .resources()
+                            .any(|ty| world.is_non_send(*ty));
+                    (index, main_thread, &mut entry.system, &mut entry.last_run)
+                })
+                .partition(|(_, main_thread, _, _)| *main_thread);
+
+            rayon::in_place_scope(|scope| {
+                for (index, _, system, last_run) in pooled {
+                    scope.spawn(move |_| {
+                        let mut commands = Commands::new();
+                        run_system(system, last_run, world, &mut commands);
+                        results.lock().unwrap().push((index, commands));
+                    });
+                }
+
+                for (index, _, system, last_run) in pinned {
+                    let mut commands = Commands::new();
+                    run_system(system, last_run, world, &mut commands);
+                    results.lock().unwrap().push((index, commands));
+                }
+            });
+        }
+
+        // Sync point, commands are applied in the order of the batch
+        // whatever the thread which ran the system.
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| {
+            batch.iter().position(|other| other == index)
+        });
+
+        results
+            .into_iter()
+            .for_each(|(_, commands)| commands.apply(world));
+        world.release_removed();
+    }
+
+    /// Split the sorted systems of a stage into batches of systems which
+    /// can run together.
+    fn batch(&self, sorted: Vec<usize>) -> Vec<Vec<usize>> {
+        let mut batches: Vec<Vec<usize>> = vec![];
+
+        for index in sorted {
+            let entry = &self.systems[index];
+
+            match batches.last_mut() {
+                Some(batch)
+                    if batch.iter().all(|other| {
+                        entry.can_run_with(&self.systems[*other])
+                    }) =>
+                {
+                    batch.push(index)
+                }
+                _ => batches.push(vec![index]),
+            }
+        }
+
+        batches
+    }
+
+    /// Topological sort of the systems of a stage, ties are broken by
+    /// insertion order.
+    /// Constraints naming a system of another stage are ignored.
+    fn sort_stage(&self, stage: Stage) -> Result<Vec<usize>, ScheduleError> {
+        let nodes: Vec<usize> = (0..self.systems.len())
+            .filter(|i| self.systems[*i].stage == stage)
+            .collect();
+
+        let named = |name: &str| -> Vec<usize> {
+            (0..nodes.len())
+                .filter(|n| self.systems[nodes[*n]].name() == name)
+                .collect()
+        };
+
+        // Edges go from a system to the systems running after it.
+        let mut edges = vec![vec![]; nodes.len()];
+        for (n, index) in nodes.iter().enumerate() {
+            let entry = &self.systems[*index];
+
+            entry.before.iter().for_each(|name| {
+                named(name).into_iter().for_each(|m| edges[n].push(m));
+            });
+            entry.after.iter().for_each(|name| {
+                named(name).into_iter().for_each(|m| edges[m].push(n));
+            });
+        }
+
+        let mut in_degree = vec![0; nodes.len()];
+        edges.iter().flatten().for_each(|m| in_degree[*m] += 1);
+
+        let mut sorted = vec![];
+        let mut done = vec![false; nodes.len()];
+
+        while let Some(n) =
+            (0..nodes.len()).find(|n| !done[*n] && in_degree[*n] == 0)
+        {
+            done[n] = true;
+            sorted.push(nodes[n]);
+            edges[n].iter().for_each(|m| in_degree[*m] -= 1);
+        }
+
+        if sorted.len() < nodes.len() {
+            let cycle = (0..nodes.len())
+                .filter(|n| !done[*n])
+                .map(|n| self.systems[nodes[n]].name())
+                .collect();
+
+            return Err(ScheduleError::Cycle(stage, cycle));
+        }
+
+        Ok(sorted)
+    }
+}
+
+impl Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let systems: Vec<_> = self
+            .systems
+            .iter()
+            .map(|entry| (entry.stage, entry.name()))
+            .collect();
+
+        f.debug_struct("Schedule")
+            .field("systems", &systems)
+            .field("fixed_timestep", &self.fixed_timestep)
+            .finish()
+    }
+}
+
+/// Returned when adding a system, to constrain when it runs.
+pub struct SystemConfig<'a> {
+    entry: &'a mut SystemEntry,
+}
+
+impl<'a> SystemConfig<'a> {
+    /// Run before the systems of type `S` of the same stage.
+    pub fn before<S: 'static>(self) -> Self {
+        self.entry.before.push(type_name::<S>());
+        self
+    }
+
+    /// Run after the systems of type `S` of the same stage.
+    pub fn after<S: 'static>(self) -> Self {
+        self.entry.after.push(type_name::<S>());
+        self
+    }
+
+    /// Skip the system when the condition is false.
+    pub fn run_if(self, condition: impl Fn(&World) -> bool + 'static) -> Self {
+        self.entry.run_if.push(Box::new(condition));
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::{NonSendMut, Res, ResMut, System};
+    use std::thread::{self, ThreadId};
+
+    #[derive(Debug, Default)]
+    struct Log(Vec<&'static str>);
+
+    #[derive(Debug, Default)]
+    struct Enabled(bool);
+
+    macro_rules! logger {
+        ($name: ident) => {
+            #[derive(Debug)]
+            struct $name;
+
+            impl<'a> System<'a> for $name {
+                type Data = ResMut<'a, Log>;
+
+                fn run(&mut self, mut log: Self::Data, _: &mut Commands) {
+                    log.0.push(stringify!($name));
+                }
+            }
+        };
+    }
+
+    logger!(A);
+    logger!(B);
+    logger!(C);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.insert_resource(Log::default());
+        world
+    }
+
+    #[test]
+    fn should_run_stages_in_order() {
+        let mut world = world();
+
+        world.add_system_to_stage(Stage::Render, A);
+        world.add_system_to_stage(Stage::PreUpdate, B);
+        world.add_system(C);
+        world.run(0.);
+
+        assert_eq!(world.resource::<Log>().0, vec!["B", "C", "A"]);
+    }
+
+    #[test]
+    fn should_order_with_constraints() {
+        let mut world = world();
+
+        world.add_system(A).after::<C>();
+        world.add_system(B);
+        world.add_system(C).after::<B>();
+        world.run(0.);
+
+        assert_eq!(world.resource::<Log>().0, vec!["B", "C", "A"]);
+    }
+
+    #[test]
+    fn should_detect_cycles() {
+        let mut world = world();
+
+        world.add_system(A).before::<B>();
+        world.add_system(B).before::<C>();
+        world.add_system(C).before::<A>();
+
+        let error = world.build_schedule().unwrap_err();
+        assert!(match error {
+            ScheduleError::Cycle(Stage::Update, systems) => systems.len() == 3,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn should_skip_systems_with_false_condition() {
+        let mut world = world();
+        world.insert_resource(Enabled(false));
+
+        world
+            .add_system(A)
+            .run_if(|world| world.resource::<Enabled>().0);
+        world.add_system(B);
+        world.run(0.);
+        world.resource_mut::<Enabled>().0 = true;
+        world.run(0.);
+
+        assert_eq!(world.resource::<Log>().0, vec!["B", "A", "B"]);
+    }
+
+    #[test]
+    fn should_run_fixed_stage_at_fixed_timestep() {
+        let mut world = world();
+        world.set_fixed_timestep(0.25);
+        world.add_system_to_stage(Stage::FixedUpdate, A);
+
+        world.run(0.5);
+        assert_eq!(world.resource::<Log>().0.len(), 2);
+
+        world.run(0.125);
+        assert_eq!(world.resource::<Log>().0.len(), 2);
+
+        world.run(0.125);
+        assert_eq!(world.resource::<Log>().0.len(), 3);
+    }
+
+    #[derive(Debug)]
+    struct Input(u32);
+    #[derive(Debug)]
+    struct Threads(Vec<ThreadId>);
+    #[derive(Debug)]
+    struct Window(Vec<ThreadId>);
+
+    #[derive(Debug)]
+    struct ReadInput;
+
+    impl<'a> System<'a> for ReadInput {
+        type Data = (Res<'a, Input>, ResMut<'a, Threads>);
+
+        fn run(&mut self, (_, mut threads): Self::Data, _: &mut Commands) {
+            threads.0.push(thread::current().id());
+        }
+    }
+
+    #[derive(Debug)]
+    struct Draw;
+
+    impl<'a> System<'a> for Draw {
+        type Data = (Res<'a, Input>, NonSendMut<'a, Window>);
+
+        fn run(&mut self, (_, mut window): Self::Data, _: &mut Commands) {
+            window.0.push(thread::current().id());
+        }
+    }
+
+    #[test]
+    fn should_batch_compatible_systems() {
+        let mut schedule = Schedule::default();
+
+        schedule.add_system(Stage::Update, Box::new(ReadInput));
+        schedule.add_system(Stage::Update, Box::new(Draw));
+        schedule.add_system(Stage::Update, Box::new(A));
+        schedule.add_system(Stage::Update, Box::new(B));
+        schedule
+            .add_system(Stage::Update, Box::new(C))
+            .after::<ReadInput>();
+        schedule.build().unwrap();
+
+        let batches = &schedule.batches.unwrap()[Stage::Update as usize];
+        assert_eq!(batches, &vec![vec![0, 1, 2], vec![3], vec![4]]);
+    }
+
+    #[test]
+    fn should_pin_non_send_systems_to_main_thread() {
+        let mut world = world();
+        world.insert_resource(Input(0));
+        world.insert_resource(Threads(vec![]));
+        world.insert_non_send(Window(vec![]));
+
+        world.add_system(ReadInput);
+        world.add_system(Draw);
+        world.run(0.);
+
+        let main_thread = thread::current().id();
+        assert_eq!(world.resource::<Window>().0, vec![main_thread]);
+        assert_eq!(world.resource::<Threads>().0.len(), 1);
+    }
+
+    #[derive(Debug)]
+    struct Peek;
+
+    impl<'a> System<'a> for Peek {
+        type Data = ResMut<'a, Window>;
+
+        fn run(&mut self, mut window: Self::Data, _: &mut Commands) {
+            window.0.push(thread::current().id());
+        }
+    }
+
+    #[test]
+    fn should_pin_systems_fetching_non_send_resources() {
+        let mut world = world();
+        world.insert_resource(Input(0));
+        world.insert_resource(Threads(vec![]));
+        world.insert_non_send(Window(vec![]));
+
+        world.add_system(ReadInput);
+        world.add_system(Peek);
+        world.run(0.);
+
+        let main_thread = thread::current().id();
+        assert_eq!(world.resource::<Window>().0, vec![main_thread]);
+    }
+
+    #[test]
+    fn should_run_on_one_thread_when_single_threaded() {
+        let mut world = world();
+        world.set_executor(Executor::SingleThreaded);
+        world.insert_resource(Input(0));
+        world.insert_resource(Threads(vec![]));
+        world.insert_non_send(Window(vec![]));
+
+        world.add_system(Draw);
+        world.add_system(ReadInput);
+        world.run(0.);
+
+        let main_thread = thread::current().id();
+        assert_eq!(world.resource::<Window>().0, vec![main_thread]);
+        assert_eq!(world.resource::<Threads>().0, vec![main_thread]);
+    }
+}
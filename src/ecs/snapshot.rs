@@ -0,0 +1,285 @@
+use super::{world::SceneIds, EntityId, World};
+use std::any::{type_name, Any, TypeId};
+use std::fmt::{self, Debug};
+
+type State = Box<dyn Any + Send + Sync>;
+type SaveFn = Box<dyn Fn(&World, EntityId) -> Option<State>>;
+type RestoreFn = Box<dyn Fn(&mut World, EntityId, Option<&State>)>;
+
+/// A component whose state lives partly outside of the world (e.g. a
+/// physic body behind a handle), saved in snapshots through its own
+/// `State`.
+/// Plain components only need `World::register_snapshot`, which clones
+/// them.
+pub trait Restorable: Sized {
+    type State: Send + Sync + 'static;
+
+    fn save(&self, world: &World) -> Self::State;
+
+    /// Write back a saved state into the living component.
+    fn restore(&mut self, state: &Self::State, world: &World);
+
+    /// Build the component again when it was removed since the snapshot,
+    /// `None` if it can't be.
+    fn rebuild(_state: &Self::State, _world: &World) -> Option<Self> {
+        None
+    }
+}
+
+struct SnapshotEntry {
+    ty: TypeId,
+    name: &'static str,
+    save: SaveFn,
+    restore: RestoreFn,
+}
+
+/// Component types saved in snapshots, components which are not
+/// registered are left untouched by `World::restore`.
+#[derive(Default)]
+pub(super) struct SnapshotRegistry {
+    entries: Vec<SnapshotEntry>,
+}
+
+impl SnapshotRegistry {
+    pub fn register<T, S>(
+        &mut self,
+        save: fn(&T, &World) -> S,
+        restore: fn(&mut T, &S, &World),
+        rebuild: fn(&S, &World) -> Option<T>,
+    ) where
+        T: Debug + Send + Sync + 'static,
+        S: Send + Sync + 'static,
+    {
+        let ty = TypeId::of::<T>();
+        self.entries.retain(|entry| entry.ty != ty);
+
+        let save = move |world: &World, id| {
+            world
+                .query::<&T>()
+                .get(id)
+                .map(|component| Box::new(save(component, world)) as State)
+        };
+
+        let restore = move |world: &mut World, id, state: Option<&State>| {
+            let state = state.and_then(|state| state.downcast_ref::<S>());
+            let restored = world
+                .query::<&mut T>()
+                .get(id)
+                .map(|mut component| {
+                    if let Some(state) = state {
+                        restore(&mut component, state, world);
+                    }
+                })
+                .is_some();
+
+            match state {
+                // Added since the snapshot.
+                None if restored => {
+                    world.remove::<T>(id);
+                }
+                // Removed since the snapshot.
+                Some(state) if !restored => {
+                    if let Some(component) = rebuild(state, world) {
+                        world.insert(id, component);
+                    }
+                }
+                _ => {}
+            }
+        };
+
+        self.entries.push(SnapshotEntry {
+            ty,
+            name: type_name::<T>(),
+            save: Box::new(save),
+            restore: Box::new(restore),
+        });
+    }
+
+    pub fn save(&self, world: &World, id: EntityId) -> Vec<(TypeId, State)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                (entry.save)(world, id).map(|state| (entry.ty, state))
+            })
+            .collect()
+    }
+
+    pub fn restore(
+        &self,
+        world: &mut World,
+        id: EntityId,
+        components: &[(TypeId, State)],
+    ) {
+        self.entries.iter().for_each(|entry| {
+            let state = components
+                .iter()
+                .find(|(ty, _)| *ty == entry.ty)
+                .map(|(_, state)| state);
+
+            (entry.restore)(world, id, state);
+        });
+    }
+}
+
+impl Debug for SnapshotRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|entry| entry.name))
+            .finish()
+    }
+}
+
+/// Saved state of the registered components of every entity, see
+/// `World::snapshot`.
+pub struct Snapshot {
+    pub(super) entities: Vec<(EntityId, Vec<(TypeId, State)>)>,
+    pub(super) scene_ids: SceneIds,
+}
+
+impl Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("entities", &self.entities.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::Entity;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug)]
+    struct Frozen;
+
+    // A component pointing to a state stored in a resource, like a physic
+    // body handle.
+    #[derive(Debug)]
+    struct Body(usize);
+    #[derive(Debug, Default)]
+    struct Bodies(Vec<f32>);
+
+    impl Restorable for Body {
+        type State = f32;
+
+        fn save(&self, world: &World) -> f32 {
+            world.resource::<Bodies>().0[self.0]
+        }
+
+        fn restore(&mut self, state: &f32, world: &World) {
+            world.resource_mut::<Bodies>().0[self.0] = *state;
+        }
+
+        fn rebuild(state: &f32, world: &World) -> Option<Self> {
+            let mut bodies = world.resource_mut::<Bodies>();
+            bodies.0.push(*state);
+            Some(Body(bodies.0.len() - 1))
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register_snapshot::<Position>();
+        world.register_snapshot_with::<Body>();
+        world.insert_resource(Bodies(vec![0.]));
+        world
+    }
+
+    #[test]
+    fn should_restore_registered_components() {
+        let mut world = world();
+        let a = world.add_entity(
+            Entity::new().with(Position(1.)).with(Body(0)).with(Frozen),
+        );
+        let snapshot = world.snapshot();
+
+        world.query::<&mut Position>().get(a).unwrap().0 = 5.;
+        world.resource_mut::<Bodies>().0[0] = 5.;
+        world.remove::<Frozen>(a);
+
+        world.restore(&snapshot);
+
+        let mut query = world.query::<&Position>();
+        assert_eq!(query.get(a), Some(&Position(1.)));
+        assert_eq!(world.resource::<Bodies>().0, [0.]);
+        // Components which aren't registered are left untouched.
+        assert!(world.query::<&Frozen>().get(a).is_none());
+    }
+
+    #[test]
+    fn should_restore_entities_and_handles() {
+        let mut world = world();
+        let a =
+            world.add_entity(Entity::from_file("level", 1).with(Position(1.)));
+        let b = world.add_entity(Entity::new().with(Position(2.)));
+        let snapshot = world.snapshot();
+
+        let c = world.add_entity(Entity::new().with(Position(3.)));
+        world.insert(c, Frozen);
+        world.despawn(a);
+        world.remove::<Position>(b);
+
+        world.restore(&snapshot);
+
+        assert!(!world.contains(c));
+        assert!(world.contains(a));
+        assert_eq!(world.scene_entity("level", 1), Some(a));
+        assert_eq!(world.entities_len(), 2);
+
+        let mut query = world.query::<&Position>();
+        assert_eq!(query.get(a), Some(&Position(1.)));
+        assert_eq!(query.get(b), Some(&Position(2.)));
+    }
+
+    #[test]
+    fn should_rebuild_components_of_despawned_entities() {
+        let mut world = world();
+        let a =
+            world.add_entity(Entity::new().with(Position(1.)).with(Body(0)));
+        let snapshot = world.snapshot();
+
+        world.resource_mut::<Bodies>().0[0] = 5.;
+        world.despawn(a);
+
+        world.restore(&snapshot);
+
+        assert!(world.contains(a));
+        let body = world.query::<&Body>().get(a).map(|body| body.0);
+        assert_eq!(body, Some(1));
+        assert_eq!(world.resource::<Bodies>().0, [5., 0.]);
+        assert_eq!(world.query::<&Position>().get(a), Some(&Position(1.)));
+    }
+
+    #[test]
+    fn should_not_hand_out_stale_handles_again() {
+        let mut world = world();
+        let a =
+            world.add_entity(Entity::from_file("level", 1).with(Position(1.)));
+        let snapshot = world.snapshot();
+
+        world.despawn(a);
+        let c = world.add_entity(Entity::new().with(Position(3.)));
+        assert_eq!(c.index(), a.index());
+
+        world.restore(&snapshot);
+
+        // The slot of `a` was reused, it comes back with a new handle.
+        let restored = world.scene_entity("level", 1).unwrap();
+        assert!(!world.contains(a));
+        assert!(!world.contains(c));
+        assert_ne!(restored, c);
+        assert_eq!(
+            world.query::<&Position>().get(restored),
+            Some(&Position(1.))
+        );
+
+        world.despawn(restored);
+        let d = world.add_entity(Entity::new());
+        assert!(!world.contains(a));
+        assert!(!world.contains(c));
+        assert_ne!(d, a);
+        assert_ne!(d, c);
+    }
+}
@@ -0,0 +1,372 @@
+use super::{
+    Commands, Fetch, NonSend, NonSendMut, Query, QueryFilter, Res, ResMut,
+    SystemTicks, World, WorldQuery,
+};
+use std::any::{type_name, TypeId};
+use std::fmt::Debug;
+
+/// Resources and components a system reads or writes.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Access {
+    resource_reads: Vec<TypeId>,
+    resource_writes: Vec<TypeId>,
+    component_reads: Vec<TypeId>,
+    component_writes: Vec<TypeId>,
+    main_thread: bool,
+}
+
+impl Access {
+    pub fn read_resource<T: 'static>(&mut self) {
+        self.resource_reads.push(TypeId::of::<T>());
+    }
+
+    pub fn write_resource<T: 'static>(&mut self) {
+        self.resource_writes.push(TypeId::of::<T>());
+    }
+
+    pub fn read_component<T: 'static>(&mut self) {
+        self.component_reads.push(TypeId::of::<T>());
+    }
+
+    pub fn write_component<T: 'static>(&mut self) {
+        self.component_writes.push(TypeId::of::<T>());
+    }
+
+    /// Pin the system to the main thread.
+    pub fn set_main_thread(&mut self) {
+        self.main_thread = true;
+    }
+
+    pub fn is_main_thread(&self) -> bool {
+        self.main_thread
+    }
+
+    /// Resources read or written.
+    pub fn resources(&self) -> impl Iterator<Item = &TypeId> {
+        self.resource_reads.iter().chain(&self.resource_writes)
+    }
+
+    /// Whether two systems can run at the same time: neither of them
+    /// writes what the other one reads or writes.
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        fn disjoint(
+            writes: &[TypeId],
+            reads: &[TypeId],
+            others: &[TypeId],
+        ) -> bool {
+            writes
+                .iter()
+                .all(|ty| !reads.contains(ty) && !others.contains(ty))
+        }
+
+        disjoint(
+            &self.resource_writes,
+            &other.resource_reads,
+            &other.resource_writes,
+        ) && disjoint(
+            &other.resource_writes,
+            &self.resource_reads,
+            &self.resource_writes,
+        ) && disjoint(
+            &self.component_writes,
+            &other.component_reads,
+            &other.component_writes,
+        ) && disjoint(
+            &other.component_writes,
+            &self.component_reads,
+            &self.component_writes,
+        )
+    }
+
+    pub fn reads_resource<T: 'static>(&self) -> bool {
+        self.resource_reads.contains(&TypeId::of::<T>())
+    }
+
+    pub fn writes_resource<T: 'static>(&self) -> bool {
+        self.resource_writes.contains(&TypeId::of::<T>())
+    }
+
+    pub fn reads_component<T: 'static>(&self) -> bool {
+        self.component_reads.contains(&TypeId::of::<T>())
+    }
+
+    pub fn writes_component<T: 'static>(&self) -> bool {
+        self.component_writes.contains(&TypeId::of::<T>())
+    }
+}
+
+/// Data fetched from the world before running a system: resources
+/// (`Res<T>`, `ResMut<T>`), queries, or tuples of those.
+pub trait SystemData<'a> {
+    /// Panics if a resource is missing or already borrowed in a
+    /// conflicting way.
+    /// `ticks` tell queries which changes happened since the last run.
+    fn fetch(world: &'a World, ticks: SystemTicks) -> Self;
+
+    /// Declare what is read and written.
+    fn access(access: &mut Access);
+}
+
+impl<'a, T: Send + Sync + 'static> SystemData<'a> for Res<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        world.resource::<T>()
+    }
+
+    fn access(access: &mut Access) {
+        access.read_resource::<T>();
+    }
+}
+
+impl<'a, T: Send + Sync + 'static> SystemData<'a> for ResMut<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        world.resource_mut::<T>()
+    }
+
+    fn access(access: &mut Access) {
+        access.write_resource::<T>();
+    }
+}
+
+impl<'a, T: 'static> SystemData<'a> for NonSend<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        NonSend::new(world.resource::<T>())
+    }
+
+    fn access(access: &mut Access) {
+        access.read_resource::<T>();
+        access.set_main_thread();
+    }
+}
+
+impl<'a, T: 'static> SystemData<'a> for NonSendMut<'a, T> {
+    fn fetch(world: &'a World, _: SystemTicks) -> Self {
+        NonSendMut::new(world.resource_mut::<T>())
+    }
+
+    fn access(access: &mut Access) {
+        access.write_resource::<T>();
+        access.set_main_thread();
+    }
+}
+
+impl<'a, Q: WorldQuery, F: QueryFilter> SystemData<'a> for Query<'a, Q, F> {
+    fn fetch(world: &'a World, ticks: SystemTicks) -> Self {
+        Query::new(world, ticks)
+    }
+
+    fn access(access: &mut Access) {
+        <Q::Fetch as Fetch<'a>>::access(access);
+        F::access(access);
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($name: ident),*) => {
+        impl<'a, $($name: SystemData<'a>),*> SystemData<'a> for ($($name,)*) {
+            #[allow(clippy::unused_unit)]
+            fn fetch(_world: &'a World, _ticks: SystemTicks) -> Self {
+                ($($name::fetch(_world, _ticks),)*)
+            }
+
+            fn access(_access: &mut Access) {
+                $($name::access(_access);)*
+            }
+        }
+    };
+}
+
+tuple_impl!();
+tuple_impl!(A);
+tuple_impl!(A, B);
+tuple_impl!(A, B, C);
+tuple_impl!(A, B, C, D);
+tuple_impl!(A, B, C, D, E);
+tuple_impl!(A, B, C, D, E, F);
+tuple_impl!(A, B, C, D, E, F, G);
+tuple_impl!(A, B, C, D, E, F, G, H);
+
+/// A system declares what it needs through its `Data`, fetched from the
+/// world before each run.
+/// Structural changes go through `commands`, applied once the system is
+/// done.
+pub trait System<'a>: Debug + Send {
+    type Data: SystemData<'a>;
+
+    fn run(&mut self, data: Self::Data, commands: &mut Commands);
+}
+
+/// Object safe version of `System`, used to store systems in the world.
+pub trait RunSystem: Debug + Send {
+    fn name(&self) -> &'static str;
+
+    fn access(&self) -> Access;
+
+    fn run_now(
+        &mut self,
+        world: &World,
+        ticks: SystemTicks,
+        commands: &mut Commands,
+    );
+}
+
+impl<S> RunSystem for S
+where
+    S: for<'a> System<'a>,
+{
+    fn name(&self) -> &'static str {
+        type_name::<S>()
+    }
+
+    fn access(&self) -> Access {
+        let mut access = Access::default();
+        <S as System<'_>>::Data::access(&mut access);
+        access
+    }
+
+    fn run_now(
+        &mut self,
+        world: &World,
+        ticks: SystemTicks,
+        commands: &mut Commands,
+    ) {
+        let data = <S as System<'_>>::Data::fetch(world, ticks);
+        self.run(data, commands);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::{Added, Changed, Entity};
+
+    #[derive(Debug)]
+    struct Gravity(f32);
+    #[derive(Debug)]
+    struct Velocity(f32);
+    #[derive(Debug)]
+    struct Mass(f32);
+
+    #[derive(Debug)]
+    struct Fall;
+
+    impl<'a> System<'a> for Fall {
+        type Data = (Res<'a, Gravity>, Query<'a, (&'a mut Velocity, &'a Mass)>);
+
+        fn run(&mut self, (gravity, mut query): Self::Data, _: &mut Commands) {
+            for (_, (mut velocity, mass)) in query.iter() {
+                velocity.0 += gravity.0 * mass.0;
+            }
+        }
+    }
+
+    #[test]
+    fn should_declare_access() {
+        let access = RunSystem::access(&Fall);
+
+        assert!(access.reads_resource::<Gravity>());
+        assert!(!access.writes_resource::<Gravity>());
+        assert!(access.writes_component::<Velocity>());
+        assert!(access.reads_component::<Mass>());
+        assert!(!access.reads_component::<Velocity>());
+    }
+
+    #[test]
+    fn should_run_system_without_window() {
+        let mut world = World::new();
+        world.insert_resource(Gravity(-10.));
+        let id =
+            world.add_entity(Entity::new().with(Velocity(0.)).with(Mass(2.)));
+
+        world.add_system(Fall);
+        world.run(0.);
+        world.run(0.);
+
+        let mut query = world.query::<&Velocity>();
+        assert_eq!(query.get(id).unwrap().0, -40.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_panic_on_missing_resource() {
+        let mut world = World::new();
+
+        world.add_system(Fall);
+        world.run(0.);
+    }
+
+    #[derive(Debug, Default)]
+    struct Seen(Vec<(usize, usize)>);
+
+    #[derive(Debug)]
+    struct Detect;
+
+    impl<'a> System<'a> for Detect {
+        type Data = (
+            ResMut<'a, Seen>,
+            Query<'a, &'a Velocity, Changed<Velocity>>,
+            Query<'a, &'a Mass, Added<Mass>>,
+        );
+
+        fn run(
+            &mut self,
+            (mut seen, mut changed, mut added): Self::Data,
+            _: &mut Commands,
+        ) {
+            seen.0.push((changed.iter().count(), added.iter().count()));
+        }
+    }
+
+    #[test]
+    fn should_detect_changes_since_last_run() {
+        let mut world = World::new();
+        world.insert_resource(Seen::default());
+        let a =
+            world.add_entity(Entity::new().with(Velocity(0.)).with(Mass(2.)));
+        let b = world.add_entity(Entity::new().with(Velocity(0.)));
+
+        world.add_system(Detect);
+        world.run(0.);
+
+        {
+            let mut query = world.query::<&mut Velocity>();
+            query.get(a).unwrap().0 = 1.;
+            // Reading through a mutable fetch doesn't mark it changed.
+            assert_eq!(query.get(b).unwrap().0, 0.);
+        }
+        world.run(0.);
+        world.run(0.);
+
+        assert_eq!(world.resource::<Seen>().0, [(2, 1), (1, 0), (0, 0)]);
+    }
+
+    #[derive(Debug)]
+    struct Damp;
+
+    impl<'a> System<'a> for Damp {
+        type Data = Query<'a, &'a mut Velocity, Changed<Velocity>>;
+
+        fn run(&mut self, mut query: Self::Data, _: &mut Commands) {
+            // Items are kept while the filter reads the ticks of the next ones.
+            let velocities: Vec<_> = query.iter().collect();
+            for (_, mut velocity) in velocities {
+                velocity.0 /= 2.;
+            }
+        }
+    }
+
+    #[test]
+    fn should_filter_changes_of_fetched_components() {
+        let mut world = World::new();
+        let a = world.add_entity(Entity::new().with(Velocity(8.)));
+        let b = world.add_entity(Entity::new().with(Velocity(4.)));
+
+        world.add_system(Damp);
+        world.run(0.);
+        world.query::<&mut Velocity>().get(a).unwrap().0 = 6.;
+        world.run(0.);
+
+        let mut query = world.query::<&Velocity>();
+        assert_eq!(query.get(a).unwrap().0, 3.);
+        assert_eq!(query.get(b).unwrap().0, 2.);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt::{self, Debug};
+use std::ops::{Deref, DerefMut};
+
+/// When a component was added and last changed, in world ticks.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ComponentTicks {
+    pub added: u32,
+    pub changed: u32,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: u32) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    pub fn is_added(&self, ticks: SystemTicks) -> bool {
+        ticks.is_newer(self.added)
+    }
+
+    pub fn is_changed(&self, ticks: SystemTicks) -> bool {
+        ticks.is_newer(self.changed)
+    }
+}
+
+/// Ticks of the running system: changes made after `last_run` are new
+/// for this system.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SystemTicks {
+    pub last_run: u32,
+    pub this_run: u32,
+}
+
+impl SystemTicks {
+    fn is_newer(&self, tick: u32) -> bool {
+        // Wrapping arithmetic, so it keeps working once the world tick
+        // overflows.
+        let age = self.this_run.wrapping_sub(tick);
+        let since_last_run = self.this_run.wrapping_sub(self.last_run);
+
+        age < since_last_run
+    }
+}
+
+/// Mutable access to a component fetched by a query, the component is
+/// marked changed when it is dereferenced mutably.
+pub struct Mut<'a, T> {
+    pub(super) value: &'a mut T,
+    pub(super) ticks: &'a mut ComponentTicks,
+    pub(super) system_ticks: SystemTicks,
+}
+
+impl<'a, T> Mut<'a, T> {
+    /// Whether the component was added since the last run of the system.
+    #[allow(unused)]
+    pub fn is_added(&self) -> bool {
+        self.ticks.is_added(self.system_ticks)
+    }
+
+    /// Whether the component was changed since the last run of the
+    /// system.
+    #[allow(unused)]
+    pub fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.system_ticks)
+    }
+}
+
+impl<'a, T> Deref for Mut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for Mut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.ticks.changed = self.system_ticks.this_run;
+        self.value
+    }
+}
+
+impl<'a, T: Debug> Debug for Mut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_compare_ticks_across_overflow() {
+        let ticks = SystemTicks {
+            last_run: u32::MAX - 1,
+            this_run: 2,
+        };
+
+        assert!(ComponentTicks::new(u32::MAX).is_changed(ticks));
+        assert!(ComponentTicks::new(1).is_changed(ticks));
+        assert!(!ComponentTicks::new(u32::MAX - 1).is_changed(ticks));
+        assert!(!ComponentTicks::new(u32::MAX - 5).is_added(ticks));
+    }
+}
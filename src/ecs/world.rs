@@ -0,0 +1,844 @@
+use super::allocator::{EntityAllocator, EntityId};
+use super::archetype::{Archetype, Column, Components};
+use super::entity::{Entity, SceneId};
+use super::events::Events;
+use super::query::{Query, QueryFilter, WorldQuery};
+use super::resources::{Res, ResMut, Resources};
+use super::schedule::{Executor, Schedule, ScheduleError, Stage, SystemConfig};
+use super::snapshot::{Restorable, Snapshot, SnapshotRegistry};
+use super::system::System;
+use super::ticks::{ComponentTicks, SystemTicks};
+use std::any::{type_name, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::mem;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+type RemoveHook = Box<dyn Fn(Box<dyn Column>, &World)>;
+
+/// Called when a component is dropped by the world, to release what it
+/// owns outside of the world (e.g. physic bodies, vertex arrays).
+#[derive(Default)]
+struct RemoveHooks {
+    hooks: HashMap<TypeId, RemoveHook>,
+    // Removed components waiting for their hook.
+    pending: Components,
+}
+
+impl Debug for RemoveHooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoveHooks")
+            .field("hooks", &self.hooks.len())
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+/// Where an entity is stored.
+#[derive(Debug, Default, Clone, Copy)]
+struct Location {
+    archetype: usize,
+    row: usize,
+}
+
+/// Runtime handles of the entities of each scene file, by scene id.
+pub(super) type SceneIds = HashMap<String, HashMap<SceneId, EntityId>>;
+
+#[derive(Debug)]
+pub struct World {
+    allocator: EntityAllocator,
+    archetypes: Vec<Archetype>,
+    // Find an archetype by its (sorted) list of component types.
+    archetype_index: HashMap<Vec<TypeId>, usize>,
+    // Location of each entity, indexed by its slot.
+    locations: Vec<Location>,
+    // Runtime handles of the entities loaded from scene files, by scene.
+    scene_ids: SceneIds,
+    schedule: Schedule,
+    // Swap the buffers of each event queue.
+    event_updates: Vec<fn(&World)>,
+    hooks: RemoveHooks,
+    resources: Resources,
+    snapshots: SnapshotRegistry,
+    // Incremented each time a system runs, components are stamped with
+    // it when they are added or changed.
+    change_tick: AtomicU32,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            allocator: EntityAllocator::default(),
+            archetypes: vec![],
+            archetype_index: HashMap::new(),
+            locations: vec![],
+            scene_ids: HashMap::new(),
+            schedule: Schedule::default(),
+            event_updates: vec![],
+            hooks: RemoveHooks::default(),
+            resources: Resources::default(),
+            snapshots: SnapshotRegistry::default(),
+            change_tick: AtomicU32::new(1),
+        }
+    }
+}
+
+// Systems running in parallel only share a `&World`: columns and resources
+// are guarded by atomic borrows, non send resources are checked against the
+// main thread, hooks and the schedule are only used with a `&mut World`.
+unsafe impl Sync for World {}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entity to the world and returns its handle.
+    /// If an entity with the same scene id already exists in its scene, its
+    /// components are replaced and it keeps its handle.
+    pub fn add_entity(&mut self, entity: Entity) -> EntityId {
+        let scene = match (entity.scene(), entity.scene_id()) {
+            (Some(scene), Some(scene_id)) => {
+                Some((String::from(scene), scene_id))
+            }
+            _ => None,
+        };
+        let existing = scene
+            .as_ref()
+            .and_then(|(scene, scene_id)| self.scene_entity(scene, *scene_id))
+            .filter(|id| self.contains(*id));
+
+        let id = match existing {
+            Some(id) => {
+                let (previous, _) = self.take_components(id);
+                self.release(previous);
+                id
+            }
+            None => self.allocator.allocate(),
+        };
+
+        if let Some((scene, scene_id)) = scene {
+            self.scene_ids
+                .entry(scene)
+                .or_default()
+                .insert(scene_id, id);
+        }
+
+        let components = entity.into_components();
+        let ticks =
+            vec![ComponentTicks::new(self.change_tick()); components.len()];
+
+        self.place(id, components, ticks);
+        id
+    }
+
+    pub fn load_entities(&mut self, entities: Vec<Entity>) {
+        entities.into_iter().for_each(|entity| {
+            self.add_entity(entity);
+        });
+    }
+
+    /// Remove the entity and all its components.
+    /// Returns false if the entity was already despawned.
+    pub fn despawn(&mut self, id: EntityId) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+
+        let (components, _) = self.take_components(id);
+        self.release(components);
+        self.allocator.free(id);
+        self.scene_ids.retain(|_, entities| {
+            entities.retain(|_, entity| *entity != id);
+            !entities.is_empty()
+        });
+
+        true
+    }
+
+    /// Add a component to a living entity, replacing the previous one
+    /// of the same type if any.
+    pub fn insert<T>(&mut self, id: EntityId, component: T) -> bool
+    where
+        T: Debug + Send + Sync + 'static,
+    {
+        if !self.contains(id) {
+            return false;
+        }
+
+        let ty = TypeId::of::<T>();
+        let column: Box<dyn Column> = Box::new(vec![component]);
+        let added = ComponentTicks::new(self.change_tick());
+        let (mut components, mut ticks) = self.take_components(id);
+
+        match components.binary_search_by(|(t, _)| t.cmp(&ty)) {
+            Ok(index) => {
+                let previous = mem::replace(&mut components[index].1, column);
+                ticks[index] = added;
+                self.release(vec![(ty, previous)]);
+            }
+            Err(index) => {
+                components.insert(index, (ty, column));
+                ticks.insert(index, added);
+            }
+        }
+
+        self.place(id, components, ticks);
+        true
+    }
+
+    /// Remove a component from an entity, releasing what it owns.
+    pub fn remove<T: 'static>(&mut self, id: EntityId) -> bool {
+        let ty = TypeId::of::<T>();
+
+        match self.take_column(id, ty) {
+            Some(column) => {
+                self.release(vec![(ty, column)]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove a component from an entity and give it back, without
+    /// calling its remove hook.
+    #[allow(unused)]
+    pub fn take<T: 'static>(&mut self, id: EntityId) -> Option<T> {
+        self.take_column(id, TypeId::of::<T>())
+            .and_then(|mut column| {
+                column
+                    .as_any_mut()
+                    .downcast_mut::<Vec<T>>()
+                    .and_then(|column| column.pop())
+            })
+    }
+
+    /// Register a hook called with each removed component of type `T`:
+    /// on despawn, on removal, or when replaced.
+    pub fn on_remove<T: 'static>(
+        &mut self,
+        hook: impl Fn(T, &World) + 'static,
+    ) {
+        let hook = move |mut column: Box<dyn Column>, world: &World| {
+            if let Some(component) = column
+                .as_any_mut()
+                .downcast_mut::<Vec<T>>()
+                .and_then(|column| column.pop())
+            {
+                hook(component, world);
+            }
+        };
+
+        self.hooks.hooks.insert(TypeId::of::<T>(), Box::new(hook));
+    }
+
+    /// Call the remove hooks on the components removed since the last
+    /// call.
+    pub fn release_removed(&mut self) {
+        let removed = mem::take(&mut self.hooks.pending);
+        let world = &*self;
+
+        removed.into_iter().for_each(|(ty, column)| {
+            if let Some(hook) = world.hooks.hooks.get(&ty) {
+                hook(column, world);
+            }
+        });
+    }
+
+    /// Save the components of type `T` in snapshots, by cloning them.
+    pub fn register_snapshot<T>(&mut self)
+    where
+        T: Clone + Debug + Send + Sync + 'static,
+    {
+        self.snapshots.register::<T, T>(
+            |component, _| component.clone(),
+            |component, state, _| *component = state.clone(),
+            |state, _| Some(state.clone()),
+        );
+    }
+
+    /// Save the components of type `T` in snapshots, through their own
+    /// state.
+    pub fn register_snapshot_with<T>(&mut self)
+    where
+        T: Restorable + Debug + Send + Sync + 'static,
+    {
+        self.snapshots
+            .register::<T, T::State>(T::save, T::restore, T::rebuild);
+    }
+
+    /// Save the registered components of all entities.
+    pub fn snapshot(&self) -> Snapshot {
+        let entities = self
+            .archetypes
+            .iter()
+            .flat_map(|archetype| archetype.entities())
+            .map(|id| (*id, self.snapshots.save(self, *id)))
+            .collect();
+
+        Snapshot {
+            entities,
+            scene_ids: self.scene_ids.clone(),
+        }
+    }
+
+    /// Go back to the saved snapshot: entities spawned since then are
+    /// despawned, despawned ones come back, and the registered components
+    /// get their saved state back.
+    /// Despawned entities only get their registered components back, with
+    /// the same handle unless their slot was reused since: handles are
+    /// never handed out twice, so they get a new one then.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let saved: HashSet<EntityId> =
+            snapshot.entities.iter().map(|(id, _)| *id).collect();
+        let spawned: Vec<EntityId> = self
+            .archetypes
+            .iter()
+            .flat_map(|archetype| archetype.entities())
+            .filter(|id| !saved.contains(id))
+            .cloned()
+            .collect();
+
+        spawned.into_iter().for_each(|id| {
+            self.despawn(id);
+        });
+
+        // Handles are revived first, so new ones don't take their slots.
+        for (id, _) in snapshot.entities.iter() {
+            if !self.contains(*id) && self.allocator.revive(*id) {
+                self.place(*id, vec![], vec![]);
+            }
+        }
+
+        // The registry is taken out of the world while restoring, so
+        // restore functions can use the world.
+        let snapshots = mem::take(&mut self.snapshots);
+        let mut renewed = HashMap::new();
+
+        for (id, components) in snapshot.entities.iter() {
+            let id = if self.contains(*id) {
+                *id
+            } else {
+                let new = self.allocator.allocate();
+                self.place(new, vec![], vec![]);
+                renewed.insert(*id, new);
+                new
+            };
+
+            snapshots.restore(self, id, components);
+        }
+
+        self.snapshots = snapshots;
+        self.scene_ids = snapshot.scene_ids.clone();
+        self.scene_ids
+            .values_mut()
+            .flat_map(HashMap::values_mut)
+            .for_each(|id| *id = renewed.get(id).cloned().unwrap_or(*id));
+        self.release_removed();
+    }
+
+    /// Add a global resource, replacing the previous one of the same
+    /// type.
+    pub fn insert_resource<T: Send + Sync + 'static>(&mut self, resource: T) {
+        self.resources.insert(resource);
+    }
+
+    /// Register the `Events<T>` resource, its buffers are swapped at the
+    /// start of each frame.
+    pub fn add_event<T: Send + Sync + 'static>(&mut self) {
+        fn update<T: Send + Sync + 'static>(world: &World) {
+            world.resource_mut::<Events<T>>().update();
+        }
+
+        self.insert_resource(Events::<T>::default());
+        self.event_updates.push(update::<T>);
+    }
+
+    /// Add a global resource which can't leave the main thread, systems
+    /// fetching it are pinned to the main thread.
+    pub fn insert_non_send<T: 'static>(&mut self, resource: T) {
+        self.resources.insert_non_send(resource);
+    }
+
+    #[allow(unused)]
+    pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+        self.resources.remove::<T>()
+    }
+
+    #[allow(unused)]
+    pub fn contains_resource<T: 'static>(&self) -> bool {
+        self.resources.contains::<T>()
+    }
+
+    /// Panics if the resource is missing or borrowed mutably.
+    pub fn resource<T: 'static>(&self) -> Res<'_, T> {
+        self.resources.get::<T>().unwrap_or_else(|| {
+            panic!("Resource {} not found.", type_name::<T>())
+        })
+    }
+
+    /// Panics if the resource is missing or already borrowed.
+    pub fn resource_mut<T: 'static>(&self) -> ResMut<'_, T> {
+        self.resources.get_mut::<T>().unwrap_or_else(|| {
+            panic!("Resource {} not found.", type_name::<T>())
+        })
+    }
+
+    /// Whether the handle points to a living entity of this world.
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.allocator.is_alive(id)
+    }
+
+    /// Runtime handle of an entity loaded from a scene file.
+    pub fn scene_entity(
+        &self,
+        scene: &str,
+        scene_id: SceneId,
+    ) -> Option<EntityId> {
+        self.scene_ids.get(scene)?.get(&scene_id).cloned()
+    }
+
+    /// Entities loaded from a scene file, with their scene id.
+    pub fn scene_entities(
+        &self,
+        scene: &str,
+    ) -> impl Iterator<Item = (SceneId, EntityId)> + '_ {
+        self.scene_ids
+            .get(scene)
+            .into_iter()
+            .flat_map(|entities| entities.iter())
+            .map(|(scene_id, id)| (*scene_id, *id))
+    }
+
+    /// Scene file the entity comes from, with its id in that file.
+    pub fn entity_scene(&self, id: EntityId) -> Option<(&str, SceneId)> {
+        self.scene_ids.iter().find_map(|(scene, entities)| {
+            entities
+                .iter()
+                .find(|(_, entity)| **entity == id)
+                .map(|(scene_id, _)| (scene.as_str(), *scene_id))
+        })
+    }
+
+    fn set_location(&mut self, id: EntityId, location: Location) {
+        let index = id.index();
+
+        if index >= self.locations.len() {
+            self.locations.resize(index + 1, Location::default());
+        }
+
+        self.locations[index] = location;
+    }
+
+    /// Store the (sorted) components of an entity and their ticks in the
+    /// matching archetype.
+    fn place(
+        &mut self,
+        id: EntityId,
+        components: Components,
+        ticks: Vec<ComponentTicks>,
+    ) {
+        let types: Vec<TypeId> = components.iter().map(|(ty, _)| *ty).collect();
+
+        let archetype = match self.archetype_index.get(&types) {
+            Some(index) => *index,
+            None => {
+                self.archetypes.push(Archetype::new(&components));
+                let index = self.archetypes.len() - 1;
+                self.archetype_index.insert(types, index);
+                index
+            }
+        };
+
+        let row = self.archetypes[archetype].push(id, components, ticks);
+        self.set_location(id, Location { archetype, row });
+    }
+
+    /// Move all the components of an entity and their ticks out of its
+    /// archetype, its handle stays alive.
+    fn take_components(
+        &mut self,
+        id: EntityId,
+    ) -> (Components, Vec<ComponentTicks>) {
+        let location = self.locations[id.index()];
+        let archetype = &mut self.archetypes[location.archetype];
+
+        let (components, ticks, moved) = archetype.take(location.row);
+
+        // The last entity of the archetype took the removed row.
+        if let Some(moved) = moved {
+            self.set_location(moved, location);
+        }
+
+        (components, ticks)
+    }
+
+    /// Take one component column out of an entity, the entity is moved
+    /// into the archetype without this component.
+    fn take_column(
+        &mut self,
+        id: EntityId,
+        ty: TypeId,
+    ) -> Option<Box<dyn Column>> {
+        let (archetype, _) = self.location(id)?;
+
+        if !self.archetypes[archetype].has(ty) {
+            return None;
+        }
+
+        let (mut components, mut ticks) = self.take_components(id);
+        let index = components.iter().position(|(t, _)| *t == ty)?;
+        let (_, column) = components.remove(index);
+        ticks.remove(index);
+
+        self.place(id, components, ticks);
+        Some(column)
+    }
+
+    /// Components with a hook are kept until `release_removed` is called,
+    /// the others are dropped right away.
+    fn release(&mut self, components: Components) {
+        let RemoveHooks { hooks, pending } = &mut self.hooks;
+
+        pending.extend(
+            components
+                .into_iter()
+                .filter(|(ty, _)| hooks.contains_key(ty)),
+        );
+    }
+
+    /// Add a system to the `Update` stage.
+    pub fn add_system<S>(&mut self, system: S) -> SystemConfig<'_>
+    where
+        S: for<'a> System<'a> + 'static,
+    {
+        self.add_system_to_stage(Stage::Update, system)
+    }
+
+    pub fn add_system_to_stage<S>(
+        &mut self,
+        stage: Stage,
+        system: S,
+    ) -> SystemConfig<'_>
+    where
+        S: for<'a> System<'a> + 'static,
+    {
+        self.schedule.add_system(stage, Box::new(system))
+    }
+
+    /// Check the ordering constraints of the systems.
+    #[allow(unused)]
+    pub fn build_schedule(&mut self) -> Result<(), ScheduleError> {
+        self.schedule.build()
+    }
+
+    /// Systems run in parallel by default.
+    #[allow(unused)]
+    pub fn set_executor(&mut self, executor: Executor) {
+        self.schedule.set_executor(executor);
+    }
+
+    pub fn fixed_timestep(&self) -> f64 {
+        self.schedule.fixed_timestep()
+    }
+
+    #[allow(unused)]
+    pub fn set_fixed_timestep(&mut self, step: f64) {
+        self.schedule.set_fixed_timestep(step);
+    }
+
+    #[allow(unused)]
+    pub fn entities_len(&self) -> usize {
+        self.allocator.len()
+    }
+
+    #[allow(unused)]
+    pub fn systems_len(&self) -> usize {
+        self.schedule.len()
+    }
+
+    /// Query all entities having the requested components.
+    pub fn query<Q: WorldQuery>(&self) -> Query<'_, Q> {
+        Query::new(self, self.ticks())
+    }
+
+    /// Same as `query`, with an additional filter such as `With<T>` or
+    /// `Without<T>`.
+    pub fn query_filtered<Q: WorldQuery, F: QueryFilter>(
+        &self,
+    ) -> Query<'_, Q, F> {
+        Query::new(self, self.ticks())
+    }
+
+    /// Current tick, used to stamp the components changed outside of
+    /// systems.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Acquire)
+    }
+
+    /// Returns the tick of the system about to run.
+    pub(super) fn increment_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::AcqRel)
+    }
+
+    // Queries made outside of systems see every change since the world
+    // was created.
+    fn ticks(&self) -> SystemTicks {
+        SystemTicks {
+            last_run: 0,
+            this_run: self.change_tick(),
+        }
+    }
+
+    pub(super) fn is_non_send(&self, ty: TypeId) -> bool {
+        self.resources.is_non_send(ty)
+    }
+
+    pub(super) fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    /// Returns the archetype and the row of the given entity.
+    pub(super) fn location(&self, id: EntityId) -> Option<(usize, usize)> {
+        if !self.contains(id) {
+            return None;
+        }
+
+        let location = self.locations[id.index()];
+        Some((location.archetype, location.row))
+    }
+
+    /// Run all the stages of the schedule, `dt` being the time elapsed
+    /// since the last frame.
+    pub fn run(&mut self, dt: f64) {
+        // The schedule is taken out of the world while running, so
+        // systems can borrow it.
+        self.event_updates.iter().for_each(|update| update(self));
+
+        let mut schedule = mem::take(&mut self.schedule);
+        schedule.run(self, dt);
+        self.schedule = schedule;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::Commands;
+
+    #[derive(Debug)]
+    struct CompA {
+        foo: bool,
+    }
+    #[derive(Debug)]
+    struct CompB {
+        bar: bool,
+    }
+
+    #[test]
+    fn should_add_entity() {
+        let mut world = World::new();
+
+        let comp_a = CompA { foo: true };
+        world.add_entity(Entity::new().with::<CompA>(comp_a));
+        assert_eq!(world.entities_len(), 1);
+
+        let comp_a = CompA { foo: true };
+        let comp_b = CompB { bar: false };
+        world.add_entity(
+            Entity::new().with::<CompA>(comp_a).with::<CompB>(comp_b),
+        );
+        assert_eq!(world.entities_len(), 2);
+    }
+
+    #[test]
+    fn should_add_system() {
+        let mut world = World::new();
+
+        #[derive(Debug)]
+        struct SystemA;
+        impl<'a> System<'a> for SystemA {
+            type Data = ();
+            fn run(&mut self, _: (), _: &mut Commands) {}
+        }
+
+        #[derive(Debug)]
+        struct SystemB;
+        impl<'a> System<'a> for SystemB {
+            type Data = ();
+            fn run(&mut self, _: (), _: &mut Commands) {}
+        }
+
+        world.add_system(SystemA);
+        assert_eq!(world.systems_len(), 1);
+        world.add_system(SystemB);
+        assert_eq!(world.systems_len(), 2);
+    }
+
+    #[test]
+    fn should_run_system() {
+        let mut world = World::new();
+
+        let comp_a = CompA { foo: true };
+        world.add_entity(Entity::new().with::<CompA>(comp_a));
+
+        let mut query = world.query::<&mut CompA>();
+        for (_, mut c) in query.iter() {
+            assert_eq!(c.foo, true);
+            c.foo = false;
+            assert_ne!(c.foo, true);
+        }
+    }
+
+    #[test]
+    fn should_visit_matching_entities_only() {
+        let mut world = World::new();
+
+        world.add_entity(Entity::new().with(CompA { foo: true }));
+        world.add_entity(
+            Entity::new()
+                .with(CompA { foo: true })
+                .with(CompB { bar: true }),
+        );
+        world.add_entity(Entity::new().with(CompB { bar: true }));
+
+        let mut query = world.query::<&CompB>();
+        let visited = query.iter().filter(|(_, b)| b.bar).count();
+
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn should_replace_entity_with_same_scene_id() {
+        let mut world = World::new();
+
+        let first = world.add_entity(
+            Entity::from_file("level", 4).with(CompA { foo: true }),
+        );
+        let other = world.add_entity(Entity::new().with(CompA { foo: false }));
+
+        world.load_entities(vec![
+            Entity::from_file("level", 4).with(CompB { bar: false })
+        ]);
+        assert_eq!(world.entities_len(), 2);
+        assert_eq!(world.scene_entity("level", 4), Some(first));
+
+        // The moved entity should still be reachable.
+        let mut query = world.query::<(&CompA, Option<&CompB>)>();
+        let entities: Vec<_> = query.iter().collect();
+        assert_eq!(entities.len(), 1);
+
+        let (entity, (a, b)) = entities[0];
+        assert_eq!(entity, other);
+        assert!(!a.foo);
+        assert!(b.is_none());
+
+        let mut query = world.query::<&CompB>();
+        assert!(query.get(first).is_some());
+    }
+
+    #[test]
+    fn should_tell_scenes_apart() {
+        let mut world = World::new();
+
+        let a = world
+            .add_entity(Entity::from_file("a", 1).with(CompA { foo: true }));
+        let b = world
+            .add_entity(Entity::from_file("b", 1).with(CompA { foo: false }));
+
+        assert_ne!(a, b);
+        assert_eq!(world.scene_entity("b", 1), Some(b));
+        assert_eq!(world.entity_scene(a), Some(("a", 1)));
+        assert_eq!(world.scene_entities("a").collect::<Vec<_>>(), [(1, a)]);
+
+        world.despawn(a);
+        assert_eq!(world.scene_entities("a").count(), 0);
+        assert_eq!(world.entity_scene(b), Some(("b", 1)));
+    }
+
+    #[test]
+    fn should_not_share_ids_between_worlds() {
+        let mut world_a = World::new();
+        let mut world_b = World::new();
+
+        let a = world_a.add_entity(
+            Entity::from_file("level", 10).with(CompA { foo: true }),
+        );
+        let b = world_b.add_entity(
+            Entity::from_file("level", 10).with(CompA { foo: false }),
+        );
+
+        // Each world starts from its own first slot.
+        assert_eq!(a, b);
+        assert_eq!(world_a.entities_len(), 1);
+        assert_eq!(world_b.entities_len(), 1);
+
+        let mut query = world_a.query::<&CompA>();
+        assert!(query.get(a).unwrap().foo);
+        let mut query = world_b.query::<&CompA>();
+        assert!(!query.get(b).unwrap().foo);
+    }
+
+    #[test]
+    fn should_despawn_entity() {
+        let mut world = World::new();
+
+        let a = world.add_entity(
+            Entity::from_file("level", 1).with(CompA { foo: true }),
+        );
+        let b = world.add_entity(Entity::new().with(CompA { foo: false }));
+
+        assert!(world.despawn(a));
+        assert!(!world.despawn(a));
+        assert!(!world.contains(a));
+        assert_eq!(world.scene_entity("level", 1), None);
+        assert_eq!(world.entities_len(), 1);
+
+        // The slot is recycled with a new generation.
+        let c = world.add_entity(Entity::new().with(CompB { bar: true }));
+        assert_eq!(c.index(), a.index());
+        assert!(world.query::<&CompB>().get(a).is_none());
+        assert!(world.query::<&CompB>().get(c).is_some());
+        assert!(!world.query::<&CompA>().get(b).unwrap().foo);
+    }
+
+    #[test]
+    fn should_insert_and_remove_components() {
+        let mut world = World::new();
+        let id = world.add_entity(Entity::new().with(CompA { foo: true }));
+
+        assert!(world.insert(id, CompB { bar: true }));
+        assert!(world.query::<(&CompA, &CompB)>().get(id).is_some());
+
+        // Replace the existing component.
+        assert!(world.insert(id, CompB { bar: false }));
+        assert!(!world.query::<&CompB>().get(id).unwrap().bar);
+
+        assert!(world.remove::<CompA>(id));
+        assert!(!world.remove::<CompA>(id));
+        assert!(world.query::<&CompA>().get(id).is_none());
+
+        let b = world.take::<CompB>(id).unwrap();
+        assert!(!b.bar);
+        assert!(world.contains(id));
+    }
+
+    #[test]
+    fn should_release_removed_components_with_resources() {
+        #[derive(Debug, Default)]
+        struct Released(Vec<bool>);
+
+        let mut world = World::new();
+        world.insert_resource(Released::default());
+        world.on_remove::<CompA>(|a, world| {
+            world.resource_mut::<Released>().0.push(a.foo);
+        });
+
+        let id = world.add_entity(Entity::new().with(CompA { foo: true }));
+        world.despawn(id);
+        assert!(world.resource::<Released>().0.is_empty());
+
+        world.release_removed();
+        assert_eq!(world.resource::<Released>().0, vec![true]);
+    }
+}
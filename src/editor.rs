@@ -1,23 +1,45 @@
-use crate::game_state::GameState;
+use crate::{
+    ecs::{Events, ReaderId, Snapshot, World},
+    events::EditorEvent,
+    window::Window,
+};
 use glutin::VirtualKeyCode;
 use std::default::Default;
 
 pub struct Editor {
+    pub enabled: bool,
     pub enabled_physics: bool,
     pub enabled_wireframe_mode: bool,
 }
 
 impl Editor {
-    pub fn check_inputs(&mut self, state: &mut GameState) {
-        let keyboard = state.window.get_keyboard_events();
+    /// Toggles are published to `events`.
+    pub fn check_inputs(
+        &mut self,
+        window: &mut Window,
+        events: &mut Events<EditorEvent>,
+    ) {
+        let keyboard = window.get_keyboard_events();
 
         if keyboard.modifiers.shift {
             keyboard.once(VirtualKeyCode::P, || {
                 self.enabled_physics = !self.enabled_physics;
+                events.send(EditorEvent::PhysicsToggled(self.enabled_physics));
             });
 
             keyboard.once(VirtualKeyCode::L, || {
                 self.enabled_wireframe_mode = !self.enabled_wireframe_mode;
+                events.send(EditorEvent::WireframeToggled(
+                    self.enabled_wireframe_mode,
+                ));
+            });
+
+            keyboard.once(VirtualKeyCode::O, || {
+                events.send(EditorEvent::SaveRequested);
+            });
+
+            keyboard.once(VirtualKeyCode::V, || {
+                events.send(EditorEvent::ValidateRequested);
             });
         }
     }
@@ -26,8 +48,110 @@ impl Editor {
 impl Default for Editor {
     fn default() -> Self {
         Self {
+            enabled: true,
             enabled_physics: true,
             enabled_wireframe_mode: false,
         }
     }
 }
+
+/// Play mode of the editor: the world is saved when physics is enabled,
+/// and restored when it is disabled again.
+#[derive(Debug)]
+pub struct PlayMode {
+    reader: ReaderId<EditorEvent>,
+    snapshot: Option<Snapshot>,
+}
+
+impl PlayMode {
+    /// The editor may start with physics enabled, the world is then saved
+    /// right away, so the first stop goes back to the loaded scenes.
+    pub fn new(world: &World) -> Self {
+        let snapshot = if world.resource::<Editor>().enabled_physics {
+            Some(world.snapshot())
+        } else {
+            None
+        };
+
+        Self {
+            reader: ReaderId::default(),
+            snapshot,
+        }
+    }
+
+    pub fn update(&mut self, world: &mut World) {
+        let toggles: Vec<bool> = world
+            .resource::<Events<EditorEvent>>()
+            .read(&mut self.reader)
+            .filter_map(|event| match event {
+                EditorEvent::PhysicsToggled(enabled) => Some(*enabled),
+                _ => None,
+            })
+            .collect();
+
+        for playing in toggles {
+            if playing {
+                self.snapshot = Some(world.snapshot());
+            } else if let Some(snapshot) = self.snapshot.take() {
+                world.restore(&snapshot);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecs::Entity;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position(f32);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register_snapshot::<Position>();
+        world.insert_resource(Editor::default());
+        world.add_event::<EditorEvent>();
+        world
+    }
+
+    fn toggle(world: &mut World, enabled: bool) {
+        world.resource_mut::<Editor>().enabled_physics = enabled;
+        world
+            .resource_mut::<Events<EditorEvent>>()
+            .send(EditorEvent::PhysicsToggled(enabled));
+    }
+
+    #[test]
+    fn should_restore_loaded_world_on_first_stop() {
+        let mut world = world();
+        let a = world.add_entity(Entity::new().with(Position(1.)));
+        let mut play_mode = PlayMode::new(&world);
+
+        world.query::<&mut Position>().get(a).unwrap().0 = 5.;
+        let b = world.add_entity(Entity::new().with(Position(2.)));
+        toggle(&mut world, false);
+        play_mode.update(&mut world);
+
+        assert!(!world.contains(b));
+        let mut query = world.query::<&Position>();
+        assert_eq!(query.get(a), Some(&Position(1.)));
+    }
+
+    #[test]
+    fn should_restore_world_saved_on_play() {
+        let mut world = world();
+        world.resource_mut::<Editor>().enabled_physics = false;
+        let a = world.add_entity(Entity::new().with(Position(1.)));
+        let mut play_mode = PlayMode::new(&world);
+
+        toggle(&mut world, true);
+        play_mode.update(&mut world);
+        world.query::<&mut Position>().get(a).unwrap().0 = 5.;
+        toggle(&mut world, false);
+        play_mode.update(&mut world);
+
+        let mut query = world.query::<&Position>();
+        assert_eq!(query.get(a), Some(&Position(1.)));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{ecs::EntityId, scene_loader::SceneError};
+use glutin::{MouseButton, VirtualKeyCode};
+
+/// Raw input, published by the window each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    KeyPressed(VirtualKeyCode),
+    KeyReleased(VirtualKeyCode),
+    MousePressed(MouseButton),
+    MouseReleased(MouseButton),
+    MouseMoved(f64, f64),
+    CloseRequested,
+}
+
+/// Published by the physic step when two entities start or stop touching.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Contact {
+    Started(EntityId, EntityId),
+    Stopped(EntityId, EntityId),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneEvent {
+    Loaded(String),
+    /// The scene file changed on disk and was loaded again.
+    Reloaded(String),
+    /// The world was written back to the scene file.
+    Saved(String),
+    /// The scene and every entity it spawned were removed.
+    Unloaded(String),
+    /// The scene file couldn't be loaded, the previous scene is kept.
+    Failed(SceneError),
+}
+
+/// Published by the asset manager once an asset queued in the background is
+/// loaded, or couldn't be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetEvent {
+    Loaded(String),
+    /// The asset changed on disk and was loaded again, in place.
+    Reloaded(String),
+    /// Name of the asset and why it failed.
+    Failed(String, String),
+}
+
+/// Changes of the loaded scenes, handled by the scene loader each frame.
+#[allow(unused)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneRequest {
+    /// Stream a scene in the background, along with the ones already
+    /// loaded.
+    Load(String),
+    Unload(String),
+    /// Load `to` then unload `from`, which is kept if `to` can't be loaded.
+    Transition {
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorEvent {
+    PhysicsToggled(bool),
+    WireframeToggled(bool),
+    /// Write the world back to the loaded scene files.
+    SaveRequested,
+    /// Check the loaded scene files and the assets they use.
+    ValidateRequested,
+}
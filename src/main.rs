@@ -2,15 +2,9 @@
 ///
 /// TODO
 ///
-/// ECS:
-///  - Data should be flat in memory.
-///  - It should be possible to have multi mutable ref of components
-///    from our systems.
-///
 /// Asset Manager:
 ///  - Re-use id for the asset storage.
 ///  - Could we do better than cloning the key?
-///  - We should guess the image extension.
 ///
 /// Rendering:
 ///  - We should have a strong separation between opengl stuff and the
@@ -27,114 +21,268 @@ mod components;
 mod constants;
 mod ecs;
 mod editor;
+mod events;
 mod fonts;
 mod game_loop;
-mod game_state;
 mod opengl;
+mod resources;
 mod scene_loader;
 mod shader;
 mod systems;
 mod time;
+mod vfs;
 mod window;
 
 use crate::{
-    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
-    ecs::World,
-    editor::Editor,
+    asset_manager::AssetManager,
+    components::{
+        Camera, Children, Collider, ColliderDesc, GlobalTransform, Light, Mesh,
+        MeshDesc, Parent, Player as PlayerComponent, RigidBody, RigidBodyDesc,
+        Transform,
+    },
+    constants::{ASSETS_PACK, ASSETS_PATH, SCREEN_HEIGHT, SCREEN_WIDTH},
+    ecs::{ComponentRegistry, Events, ReaderId, Stage, World},
+    editor::{Editor, PlayMode},
+    events::{
+        AssetEvent, Contact, EditorEvent, InputEvent, SceneEvent, SceneRequest,
+    },
     game_loop::GameLoop,
-    game_state::GameState,
     opengl::OpenGL,
+    resources::{DebugInfo, PhysicWorld, RenderContext},
     scene_loader::SceneLoader,
-    shader::Shader,
-    systems::{EditorCamera, Physic, Player, Renderer},
+    systems::{
+        EditorCamera, Physic, PhysicStep, Player, Renderer,
+        TransformPropagation,
+    },
+    time::Time,
+    vfs::{pack_dir, Vfs},
+    window::Window,
 };
 use gui::GUI;
+use std::env;
+use std::path::{Path, PathBuf};
 
 fn main() -> Result<(), notify::Error> {
-    let mut state = GameState::new();
+    // `engine pack [asset directory] [pack file]` bundles the assets in one
+    // pack for release builds, instead of running the game. The pack is
+    // written next to the executable by default, where the game reads it.
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() == Some("pack") {
+        let root = args.next().unwrap_or_else(|| String::from(ASSETS_PATH));
+        let out = match args.next() {
+            Some(out) => PathBuf::from(out),
+            None => env::current_exe()?.with_file_name(ASSETS_PACK),
+        };
+        let count = pack_dir(Path::new(&root), &out)?;
+        println!("Packed {} files into {}", count, out.display());
+        return Ok(());
+    }
+
     let mut game_loop = GameLoop::new();
     let mut world = World::new();
-    let mut editor = Editor::default();
+
+    // The window owns the GL context, it should be created before any
+    // other GPU resource.
+    // Resources using it are pinned to the main thread.
+    world.insert_non_send(Window::new());
+
+    // Assets are read from the pack next to the executable, or else from
+    // the asset directory.
+    let mut asset_manager = AssetManager::new(Vfs::assets()?);
+    asset_manager.watch(2)?;
+    world.insert_non_send(RenderContext::new(&mut asset_manager));
+    world.insert_non_send(asset_manager);
+    world.insert_non_send(DebugInfo::new());
+
+    let mut physic_world = PhysicWorld::default();
+    physic_world.set_timestep(world.fixed_timestep() as f32);
+    world.insert_non_send(physic_world);
+
+    world.insert_resource(Time::default());
+    world.insert_resource(Editor::default());
+
+    world.add_event::<InputEvent>();
+    world.add_event::<Contact>();
+    world.add_event::<SceneEvent>();
+    world.add_event::<SceneRequest>();
+    world.add_event::<EditorEvent>();
+    world.add_event::<AssetEvent>();
+
+    // Release what components own outside of the world when they
+    // are removed.
+    world.on_remove::<RigidBody>(|rigid_body, world| {
+        rigid_body.release(&mut world.resource_mut::<PhysicWorld>());
+    });
+    world.on_remove::<Collider>(|collider, world| {
+        collider.release(&mut world.resource_mut::<PhysicWorld>());
+    });
+    world.on_remove::<Mesh>(|mesh, _| mesh.release());
+
+    // Components saved when entering play mode.
+    world.register_snapshot::<Transform>();
+    world.register_snapshot::<GlobalTransform>();
+    world.register_snapshot::<Parent>();
+    world.register_snapshot::<Children>();
+    world.register_snapshot::<Light>();
+    world.register_snapshot::<PlayerComponent>();
+    world.register_snapshot::<MeshDesc>();
+    world.register_snapshot::<RigidBodyDesc>();
+    world.register_snapshot::<ColliderDesc>();
+    world.register_snapshot_with::<Mesh>();
+    world.register_snapshot_with::<RigidBody>();
+    world.register_snapshot_with::<Collider>();
+
+    // Components which can be read from and written to files by name,
+    // scene files are made of them.
+    let mut registry = ComponentRegistry::default();
+    registry.register_default::<Transform>();
+    registry.register_default::<Camera>();
+    registry.register_default::<PlayerComponent>();
+    registry.register::<Light>();
+    registry.register::<MeshDesc>();
+    registry.register::<RigidBodyDesc>();
+    registry.register::<ColliderDesc>();
+    world.insert_resource(registry);
 
     let _ = GUI::default();
 
-    let mut scene_loader = SceneLoader::new(2);
-    scene_loader.set_scene("scene_1.ron");
+    let mut scene_loader =
+        SceneLoader::new(2, world.resource::<AssetManager>().vfs())?;
 
-    // Load scene for the first time.
-    scene_loader.load(&mut world, &mut state);
+    // The camera and the player stay loaded, levels come and go.
+    scene_loader.load(&mut world, "persistent.ron");
+    scene_loader.load(&mut world, "scene_1.ron");
+    let mut play_mode = PlayMode::new(&world);
 
     // Add systems
+    world
+        .add_system_to_stage(Stage::FixedUpdate, PhysicStep::default())
+        .run_if(|world| world.resource::<Editor>().enabled_physics);
     world.add_system(EditorCamera::default());
     world.add_system(Player::default());
-    world.add_system(Physic::default());
-    world.add_system(Renderer::default());
+    world.add_system(Physic::default()).after::<Player>();
+    world.add_system_to_stage(
+        Stage::PostUpdate,
+        TransformPropagation::default(),
+    );
+    world.add_system_to_stage(Stage::Render, Renderer::default());
 
-    game_loop.start(|time, fps| {
-        state.window.capture();
-        state.time = time.clone();
+    // Assets which fail to load are shown by the HUD.
+    let mut asset_events = ReaderId::default();
 
-        editor.check_inputs(&mut state);
+    game_loop.start(|time, fps| {
+        world
+            .resource_mut::<Window>()
+            .capture(&mut world.resource_mut::<Events<InputEvent>>());
+        *world.resource_mut::<Time>() = time.clone();
 
-        state.physic_world.set_timestep(time.dt as f32);
-        scene_loader.watch(&mut world, &mut state);
+        world.resource_mut::<Editor>().check_inputs(
+            &mut world.resource_mut::<Window>(),
+            &mut world.resource_mut::<Events<EditorEvent>>(),
+        );
 
-        if editor.enabled_physics {
-            state.physic_world.step();
+        play_mode.update(&mut world);
+        world
+            .resource_mut::<AssetManager>()
+            .update(&mut world.resource_mut::<Events<AssetEvent>>());
+        for event in world
+            .resource::<Events<AssetEvent>>()
+            .read(&mut asset_events)
+        {
+            world.resource_mut::<DebugInfo>().on_asset(event);
         }
+        scene_loader.watch(&mut world);
+        // Assets of the meshes despawned this frame.
+        world.resource_mut::<AssetManager>().release_unused();
 
-        let running = !state.window.should_close;
+        let running = !world.resource::<Window>().should_close;
 
         // First render pass.
         // We render to the scene fbo.
-        let (fbo, tex) = state.scene_fbo;
+        let (fbo, tex) = world.resource::<RenderContext>().scene_fbo;
         OpenGL::use_fbo(fbo);
         OpenGL::set_depth_buffer(true);
         OpenGL::clear_color((0., 0., 0.));
 
-        if editor.enabled_wireframe_mode {
+        if world.resource::<Editor>().enabled_wireframe_mode {
             OpenGL::line_mode();
         }
-        world.run(&mut state);
+        world.run(time.dt);
         OpenGL::fill_mode();
 
+        let context = world.resource::<RenderContext>();
+        let asset_manager = world.resource::<AssetManager>();
+        let debug_info = world.resource::<DebugInfo>();
+
         // Skybox pass.
-        unsafe { gl::DepthFunc(gl::LEQUAL) }
-        let shader = state.asset_manager.get_ressource::<Shader>("skybox");
-        OpenGL::use_shader(shader.id);
-        shader.set_int("skybox", 0);
-        OpenGL::draw_skybox(state.skybox.0, state.skybox.2, state.skybox.1);
-        unsafe { gl::DepthFunc(gl::LESS) }
+        if let Some(shader) = asset_manager.get(&context.skybox_shader) {
+            unsafe { gl::DepthFunc(gl::LEQUAL) }
+            OpenGL::use_shader(shader.id);
+            shader.set_int("skybox", 0);
+            OpenGL::draw_skybox(
+                context.skybox.0,
+                context.skybox.2,
+                context.skybox.1,
+            );
+            unsafe { gl::DepthFunc(gl::LESS) }
+        }
 
         // For post effects, we're using the default framebuffer.
         OpenGL::use_fbo(0);
         OpenGL::clear_color((1., 1., 1.));
 
-        let shader =
-            state.asset_manager.get_ressource::<Shader>("screen_output");
-        OpenGL::set_depth_buffer(false);
-        OpenGL::use_shader(shader.id);
-        shader.set_int("screen", 0);
-        OpenGL::draw(state.screen_vao, Some(tex), 6);
+        if let Some(shader) = asset_manager.get(&context.screen_shader) {
+            OpenGL::set_depth_buffer(false);
+            OpenGL::use_shader(shader.id);
+            shader.set_int("screen", 0);
+            OpenGL::draw(context.screen_vao, Some(tex), 6);
+        }
 
         // HUD render pass.
         // For now, it is the last render pass 'cause it is easy to debug with
         // lines mode.
-        let text_shader = state.asset_manager.get_ressource::<Shader>("text");
-        state.debug_text.render(
-            format!("fps: {}", fps.round()).as_str(),
-            text_shader,
-            (SCREEN_WIDTH - 105., SCREEN_HEIGHT - 60.),
-            (255., 0., 0.),
-        );
-        state.debug_text.render(
-            state.cam_pos.as_str(),
-            text_shader,
-            (SCREEN_WIDTH - 170., 0.),
-            (255., 0., 0.),
-        );
+        if let Some(text_shader) = asset_manager.get(&context.text_shader) {
+            debug_info.text.render(
+                format!("fps: {}", fps.round()).as_str(),
+                text_shader,
+                (SCREEN_WIDTH - 105., SCREEN_HEIGHT - 60.),
+                (255., 0., 0.),
+            );
+            debug_info.text.render(
+                debug_info.cam_pos.as_str(),
+                text_shader,
+                (SCREEN_WIDTH - 170., 0.),
+                (255., 0., 0.),
+            );
+            if let Some(loading) = debug_info.loading {
+                debug_info.text.render(
+                    format!("loading: {}%", (loading * 100.).round()).as_str(),
+                    text_shader,
+                    (10., SCREEN_HEIGHT - 100.),
+                    (255., 255., 255.),
+                );
+            }
+            // Shader logs can be long, only their first line fits.
+            if let Some((name, err)) = debug_info.asset_error.as_ref() {
+                let err = err.lines().next().unwrap_or_default();
+                debug_info.text.render(
+                    format!("{}: {}", name, err).as_str(),
+                    text_shader,
+                    (10., SCREEN_HEIGHT - 140.),
+                    (255., 0., 0.),
+                );
+            }
+            if let Some(scene_error) = debug_info.scene_error.as_ref() {
+                debug_info.text.render(
+                    scene_error,
+                    text_shader,
+                    (10., SCREEN_HEIGHT - 60.),
+                    (255., 0., 0.),
+                );
+            }
+        }
 
-        state.window.swap_gl();
+        world.resource::<Window>().swap_gl();
         running
     });
 
@@ -47,6 +47,16 @@ impl OpenGL {
         vao
     }
 
+    /// Delete Vertex Array Object.
+    pub fn delete_vao(vao: u32) {
+        unsafe { gl::DeleteVertexArrays(1, &vao) }
+    }
+
+    /// Delete a texture from the GPU.
+    pub fn delete_texture(texture: u32) {
+        unsafe { gl::DeleteTextures(1, &texture) }
+    }
+
     /// Generate buffer.
     pub fn gen_buffer() -> u32 {
         let mut id = 0;
@@ -326,25 +336,13 @@ impl OpenGL {
         vao
     }
 
-    // Create a little plane.
-    // Return vao and texture id and positions.
-    pub fn gen_plane() -> (u32, i32, bool) {
-        #[cfg_attr(rustfmt, rustfmt_skip)]
-        let vertices: [f32; 48] = [
-            // vertex, tex, normal.
-            -1., 0., 1., 0., 5., 0., 1., 0.,
-            1., 0., -1., 5., 0.,0., 1., 0.,
-            1., 0., 1., 5., 5., 0., 1., 0.,
-            -1., 0., 1., 0., 5.,0., 1., 0.,
-            -1., 0., -1., 0., 0.,0., 1., 0.,
-            1., 0., -1., 5., 0.,0., 1., 0.,
-        ];
-
-        // let indices: [i32; 6] = [0, 1, 3, 1, 2, 3];
-
+    /// Send vertices to the GPU, each one made of a position, a texture
+    /// coordinate and a normal.
+    /// Return the vao.
+    pub fn load_mesh(vertices: &[f32]) -> u32 {
         let vao = OpenGL::gen_vao();
         let vbo = OpenGL::gen_buffer();
-        // let ebo = OpenGL::gen_buffer();
+        let stride = 8 * mem::size_of::<GLfloat>() as GLsizei;
 
         unsafe {
             gl::BindVertexArray(vao);
@@ -353,20 +351,10 @@ impl OpenGL {
             gl::BufferData(
                 gl::ARRAY_BUFFER,
                 (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                &vertices[0] as *const f32 as *const c_void,
+                vertices.as_ptr() as *const c_void,
                 gl::STATIC_DRAW,
             );
 
-            // gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-            // gl::BufferData(
-            //     gl::ELEMENT_ARRAY_BUFFER,
-            //     (indices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-            //     &indices[0] as *const i32 as *const c_void,
-            //     gl::STATIC_DRAW,
-            // );
-
-            let stride = 8 * mem::size_of::<GLfloat>() as GLsizei;
-
             gl::VertexAttribPointer(
                 0,
                 3,
@@ -392,91 +380,13 @@ impl OpenGL {
                 3,
                 gl::FLOAT,
                 gl::FALSE,
-                8 * mem::size_of::<GLfloat>() as GLsizei,
-                (5 * mem::size_of::<GLfloat>()) as *const c_void,
-            );
-            gl::EnableVertexAttribArray(2);
-        }
-
-        (vao, 6, false)
-    }
-
-    // Create a little cube
-    // Return vao and texture id and positions.
-    pub fn gen_cube() -> (u32, i32, bool) {
-        // position + text coord + normal
-        let vertices: [f32; 288] = [
-            -0.5, -0.5, -0.5, 0.0, 0.0, 0., 0., -1., 0.5, -0.5, -0.5, 1.0, 0.0,
-            0., 0., -1., 0.5, 0.5, -0.5, 1.0, 1.0, 0., 0., -1., 0.5, 0.5, -0.5,
-            1.0, 1.0, 0., 0., -1., -0.5, 0.5, -0.5, 0.0, 1.0, 0., 0., -1.,
-            -0.5, -0.5, -0.5, 0.0, 0.0, 0., 0., -1., -0.5, -0.5, 0.5, 0.0, 0.0,
-            0., 0., 1., 0.5, -0.5, 0.5, 1.0, 0.0, 0., 0., 1., 0.5, 0.5, 0.5,
-            1.0, 1.0, 0., 0., 1., 0.5, 0.5, 0.5, 1.0, 1.0, 0., 0., 1., -0.5,
-            0.5, 0.5, 0.0, 1.0, 0., 0., 1., -0.5, -0.5, 0.5, 0.0, 0.0, 0., 0.,
-            1., -0.5, 0.5, 0.5, 1.0, 0.0, -1., 0., 0., -0.5, 0.5, -0.5, 1.0,
-            1.0, -1., 0., 0., -0.5, -0.5, -0.5, 0.0, 1.0, -1., 0., 0., -0.5,
-            -0.5, -0.5, 0.0, 1.0, -1., 0., 0., -0.5, -0.5, 0.5, 0.0, 0.0, -1.,
-            0., 0., -0.5, 0.5, 0.5, 1.0, 0.0, -1., 0., 0., 0.5, 0.5, 0.5, 1.0,
-            0.0, 1., 0., 0., 0.5, 0.5, -0.5, 1.0, 1.0, 1., 0., 0., 0.5, -0.5,
-            -0.5, 0.0, 1.0, 1., 0., 0., 0.5, -0.5, -0.5, 0.0, 1.0, 1., 0., 0.,
-            0.5, -0.5, 0.5, 0.0, 0.0, 1., 0., 0., 0.5, 0.5, 0.5, 1.0, 0.0, 1.,
-            0., 0., -0.5, -0.5, -0.5, 0.0, 1.0, 0., -1., 0., 0.5, -0.5, -0.5,
-            1.0, 1.0, 0., -1., 0., 0.5, -0.5, 0.5, 1.0, 0.0, 0., -1., 0., 0.5,
-            -0.5, 0.5, 1.0, 0.0, 0., -1., 0., -0.5, -0.5, 0.5, 0.0, 0.0, 0.,
-            -1., 0., -0.5, -0.5, -0.5, 0.0, 1.0, 0., -1., 0., -0.5, 0.5, -0.5,
-            0.0, 1.0, 0., 1., 0., 0.5, 0.5, -0.5, 1.0, 1.0, 0., 1., 0., 0.5,
-            0.5, 0.5, 1.0, 0.0, 0., 1., 0., 0.5, 0.5, 0.5, 1.0, 0.0, 0., 1.,
-            0., -0.5, 0.5, 0.5, 0.0, 0.0, 0., 1., 0., -0.5, 0.5, -0.5, 0.0,
-            1.0, 0., 1., 0.,
-        ];
-
-        let vao = OpenGL::gen_vao();
-        let vbo = OpenGL::gen_buffer();
-
-        unsafe {
-            gl::BindVertexArray(vao);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                &vertices[0] as *const f32 as *const c_void,
-                gl::STATIC_DRAW,
-            );
-
-            gl::VertexAttribPointer(
-                0,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                8 * mem::size_of::<GLfloat>() as GLsizei,
-                ptr::null(),
-            );
-            gl::EnableVertexAttribArray(0);
-
-            gl::VertexAttribPointer(
-                1,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                8 * mem::size_of::<GLfloat>() as GLsizei,
-                (3 * mem::size_of::<GLfloat>()) as *const c_void,
-            );
-
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(
-                2,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                8 * mem::size_of::<GLfloat>() as GLsizei,
+                stride,
                 (5 * mem::size_of::<GLfloat>()) as *const c_void,
             );
-
             gl::EnableVertexAttribArray(2);
         }
 
-        (vao, 36, false)
+        vao
     }
 
     pub fn gen_skybox() -> (u32, i32, bool) {
@@ -770,6 +680,25 @@ impl OpenGL {
         id
     }
 
+    /// Replace the pixels of a texture, its id stays the same.
+    pub fn update_2d_texture(id: u32, width: i32, height: i32, image: &[u8]) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                image.as_ptr() as *const c_void,
+            );
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+    }
+
     pub fn use_shader(id: u32) {
         unsafe { gl::UseProgram(id) }
     }
@@ -0,0 +1,43 @@
+use crate::{events::AssetEvent, fonts::GameFont};
+
+/// Text displayed by the HUD render pass.
+pub struct DebugInfo {
+    pub text: GameFont,
+    pub cam_pos: String,
+    /// Last error of the scene loader, until the scene loads again.
+    pub scene_error: Option<String>,
+    /// Progress of the scenes streamed in the background, from 0 to 1.
+    pub loading: Option<f32>,
+    /// Last asset which couldn't be loaded, and why, until it loads.
+    pub asset_error: Option<(String, String)>,
+}
+
+impl DebugInfo {
+    pub fn new() -> Self {
+        Self {
+            text: GameFont::new(28.),
+            cam_pos: String::default(),
+            scene_error: None,
+            loading: None,
+            asset_error: None,
+        }
+    }
+
+    /// Keep the last asset error, until that asset loads.
+    pub fn on_asset(&mut self, event: &AssetEvent) {
+        match event {
+            AssetEvent::Failed(name, err) => {
+                self.asset_error = Some((name.clone(), err.clone()))
+            }
+            AssetEvent::Loaded(name) | AssetEvent::Reloaded(name) => {
+                let fixed = match &self.asset_error {
+                    Some((failed, _)) => failed == name,
+                    None => false,
+                };
+                if fixed {
+                    self.asset_error = None;
+                }
+            }
+        }
+    }
+}
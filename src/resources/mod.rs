@@ -0,0 +1,7 @@
+mod debug_info;
+mod physic_world;
+mod render_context;
+
+pub use debug_info::DebugInfo;
+pub use physic_world::PhysicWorld;
+pub use render_context::RenderContext;
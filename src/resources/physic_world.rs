@@ -0,0 +1,30 @@
+use nalgebra_glm as glm;
+use nphysics3d::world::World;
+use std::ops::{Deref, DerefMut};
+
+/// The nphysics world, as a resource.
+pub struct PhysicWorld(World<f32>);
+
+impl Default for PhysicWorld {
+    fn default() -> Self {
+        let mut world = World::new();
+        // Earth gravity.
+        world.set_gravity(glm::TVec3::y() * -9.807);
+
+        PhysicWorld(world)
+    }
+}
+
+impl Deref for PhysicWorld {
+    type Target = World<f32>;
+
+    fn deref(&self) -> &World<f32> {
+        &self.0
+    }
+}
+
+impl DerefMut for PhysicWorld {
+    fn deref_mut(&mut self) -> &mut World<f32> {
+        &mut self.0
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    asset_manager::{AssetManager, Handle, Texture},
+    constants::{
+        CAMERA_UBO_BINDING, LIGHTS_UBO_BINDING, SCREEN_HEIGHT, SCREEN_WIDTH,
+    },
+    opengl::OpenGL,
+    shader::Shader,
+};
+use nalgebra_glm as glm;
+
+/// GPU objects shared by the render passes.
+pub struct RenderContext {
+    pub camera_ubo: u32,
+    pub lights_ubo: u32,
+    pub screen_vao: u32,
+    pub scene_fbo: (u32, u32),
+    pub skybox: (u32, i32, u32),
+    pub skybox_shader: Handle<Shader>,
+    pub screen_shader: Handle<Shader>,
+    pub text_shader: Handle<Shader>,
+    // Shaders of the scene meshes, kept loaded even when no mesh uses them.
+    #[allow(unused)]
+    materials: Vec<Handle<Shader>>,
+}
+
+impl RenderContext {
+    /// Needs a GL context, the window should be created first.
+    /// Default shaders and skybox textures are added to the asset manager.
+    pub fn new(asset_manager: &mut AssetManager) -> Self {
+        let projection = glm::perspective(
+            45_f32.to_radians(),
+            (SCREEN_WIDTH / SCREEN_HEIGHT) as f32,
+            0.1,
+            100.,
+        );
+
+        let camera_ubo = OpenGL::create_camera_ubo(CAMERA_UBO_BINDING);
+        OpenGL::set_mat4_to_ubo(projection, camera_ubo, 0);
+
+        let lights_ubo = OpenGL::create_lights_ubo(LIGHTS_UBO_BINDING);
+
+        let materials = vec![
+            asset_manager.add_shader("default", "default", "default"),
+            asset_manager.add_shader(
+                "default_material",
+                "default_material",
+                "default_material",
+            ),
+            asset_manager.add_shader("light", "default", "default"),
+            asset_manager.add_shader("outline", "default_material", "outline"),
+        ];
+        // TODO: Should rename those shaders.
+        let screen_shader =
+            asset_manager.add_shader("screen_output", "quad", "quad");
+        let skybox_shader =
+            asset_manager.add_shader("skybox", "skybox", "skybox");
+        // For text purpose.
+        let text_shader = asset_manager.add_shader("text", "text", "text");
+
+        // Load skybox data, in the order of the cubemap faces.
+        // Only needed to build the cubemap, they are unloaded once the
+        // handles are dropped.
+        let skybox_textures: Vec<_> = [
+            "skybox_lf.png",
+            "skybox_rt.png",
+            "skybox_up.png",
+            "skybox_dn.png",
+            "skybox_ft.png",
+            "skybox_bk.png",
+        ]
+        .iter()
+        .map(|texture| asset_manager.load::<Texture>(texture))
+        .collect();
+        asset_manager.wait(&skybox_textures);
+
+        let skybox: Vec<&Texture> = skybox_textures
+            .iter()
+            .filter_map(|texture| asset_manager.get(texture))
+            .collect();
+
+        let screen_vao = OpenGL::gen_screen_quad();
+        let scene_fbo = OpenGL::create_fbo();
+
+        let skybox = {
+            let tex = OpenGL::load_cubemap(skybox);
+            let data = OpenGL::gen_skybox();
+
+            (data.0, data.1, tex)
+        };
+
+        Self {
+            camera_ubo,
+            lights_ubo,
+            screen_vao,
+            scene_fbo,
+            skybox,
+            skybox_shader,
+            screen_shader,
+            text_shader,
+            materials,
+        }
+    }
+}
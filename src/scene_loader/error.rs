@@ -0,0 +1,110 @@
+use crate::ecs::SceneId;
+use std::fmt::{self, Display};
+
+/// Error of a scene or a prefab file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneError {
+    pub file: String,
+    /// Line and column, both starting at 1.
+    pub position: Option<(usize, usize)>,
+    /// Entity being read, or whose components reference a missing asset.
+    pub entity: Option<SceneId>,
+    pub kind: SceneErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneErrorKind {
+    /// The file can't be read.
+    Io(String),
+    /// The file isn't a valid scene, or prefab.
+    Syntax(String),
+    MissingTexture(String),
+    MissingShader(String),
+}
+
+impl SceneError {
+    pub fn new(kind: SceneErrorKind) -> Self {
+        Self {
+            file: String::default(),
+            position: None,
+            entity: None,
+            kind,
+        }
+    }
+
+    /// Error of the deserializer, `remainder` is the part of the source it
+    /// didn't read.
+    pub fn from_ron(
+        err: ron::de::Error,
+        source: &str,
+        remainder: &str,
+        entity: Option<SceneId>,
+    ) -> Self {
+        let message = err.to_string();
+
+        // Only syntax errors know where they are, other ones happened
+        // where the deserializer stopped.
+        let (message, position) = match err {
+            ron::de::Error::Parser(_, position) => {
+                let prefix = format!("{}: ", position);
+                let message = message.trim_start_matches(prefix.as_str());
+                (String::from(message), (position.line, position.col))
+            }
+            _ => {
+                let offset = source.len().saturating_sub(remainder.len());
+                (message, line_col(source, offset))
+            }
+        };
+
+        Self {
+            position: Some(position),
+            entity,
+            ..Self::new(SceneErrorKind::Syntax(message))
+        }
+    }
+
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.file = String::from(file);
+        self
+    }
+
+    pub fn with_entity(mut self, entity: SceneId) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+
+        if let Some((line, col)) = self.position {
+            write!(f, ":{}:{}", line, col)?;
+        }
+        if let Some(entity) = self.entity {
+            write!(f, ", entity {}", entity)?;
+        }
+
+        match &self.kind {
+            SceneErrorKind::Io(err) => write!(f, ": {}", err),
+            SceneErrorKind::Syntax(err) => write!(f, ": {}", err),
+            SceneErrorKind::MissingTexture(name) => {
+                write!(f, ": missing texture `{}`", name)
+            }
+            SceneErrorKind::MissingShader(name) => {
+                write!(f, ": missing shader `{}`", name)
+            }
+        }
+    }
+}
+
+/// Line and column of a byte offset in the source.
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset).unwrap_or(source);
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
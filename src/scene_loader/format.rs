@@ -0,0 +1,1163 @@
+use super::{Prefab, PrefabInstance, Prefabs, SceneError};
+use crate::{
+    components::{remove_parent, set_parent, Children, Parent},
+    ecs::{
+        ComponentRegistration, ComponentRegistry, Entity, EntityId, SceneId,
+        World,
+    },
+};
+use ron::ser::PrettyConfig;
+use serde::de::{
+    self, value::MapAccessDeserializer, Deserialize, DeserializeSeed,
+    Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{
+    self, Serialize, SerializeStruct, SerializeStructVariant, Serializer,
+};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter;
+use std::mem;
+
+/// Name of the entry of a component list instantiating a prefab.
+const PREFAB: &str = "Prefab";
+const PREFAB_FIELDS: &[&str] = &["path", "overrides"];
+
+/// Read a scene file, the nested entities are flattened, each one with
+/// the id of its parent.
+/// Entities remember the name of their scene.
+pub fn parse_scene(
+    scene: &str,
+    source: &str,
+    registry: &ComponentRegistry,
+    prefabs: &Prefabs,
+) -> Result<Vec<(Entity, Option<SceneId>)>, SceneError> {
+    let entity = Cell::new(None);
+    let context = Context {
+        scene,
+        registry,
+        prefabs,
+        entity: &entity,
+    };
+
+    let mut deserializer = ron::de::Deserializer::from_str(source);
+    let scene = SceneSeed(context)
+        .deserialize(&mut deserializer)
+        .and_then(|scene| deserializer.end().map(|()| scene))
+        .map_err(|err| {
+            let remainder = deserializer.remainder();
+            SceneError::from_ron(err, source, &remainder, entity.get())
+                .in_file(scene)
+        })?;
+
+    let mut entities = vec![];
+    scene
+        .into_iter()
+        .for_each(|entity| entity.flatten(None, &mut entities));
+
+    Ok(entities)
+}
+
+/// Read a prefab file, `(components: [...])`.
+/// Prefabs can't be nested.
+pub fn parse_prefab(
+    name: &str,
+    source: &str,
+    registry: &ComponentRegistry,
+) -> Result<Prefab, SceneError> {
+    let prefabs = Prefabs::default();
+    let entity = Cell::new(None);
+    let context = Context {
+        scene: name,
+        registry,
+        prefabs: &prefabs,
+        entity: &entity,
+    };
+
+    let mut deserializer = ron::de::Deserializer::from_str(source);
+    PrefabSeed(context)
+        .deserialize(&mut deserializer)
+        .and_then(|entity| deserializer.end().map(|()| entity))
+        .and_then(|entity| {
+            Prefab::new(entity, registry).map_err(de::Error::custom)
+        })
+        .map_err(|err| {
+            let remainder = deserializer.remainder();
+            SceneError::from_ron(err, source, &remainder, None).in_file(name)
+        })
+}
+
+/// Attach the entities of a scene to their parent, given by scene ids,
+/// and detach the ones which are no longer nested.
+pub fn link_scene(
+    world: &mut World,
+    scene: &str,
+    links: &[(SceneId, Option<SceneId>)],
+) {
+    for (child, parent) in links {
+        let child = match world.scene_entity(scene, *child) {
+            Some(child) => child,
+            None => continue,
+        };
+        let parent =
+            parent.and_then(|parent| world.scene_entity(scene, parent));
+        let current = world.query::<&Parent>().get(child).map(|p| p.0);
+
+        match parent {
+            Some(parent) if current != Some(parent) => {
+                set_parent(world, child, parent)
+            }
+            None if current.is_some() => remove_parent(world, child),
+            _ => {}
+        }
+    }
+}
+
+/// Write the entities loaded from a scene file with their registered
+/// components, in the format read by `parse_scene`.
+/// Entities which don't come from this file are left out, the ones
+/// instantiated from a prefab are written with the components which
+/// differ from it.
+pub fn write_scene(
+    world: &World,
+    scene: &str,
+    registry: &ComponentRegistry,
+    prefabs: &Prefabs,
+) -> Result<String, ron::ser::Error> {
+    let scene_ids = world
+        .scene_entities(scene)
+        .map(|(scene_id, id)| (id, scene_id))
+        .collect();
+    let writer = Writer {
+        world,
+        registry,
+        prefabs,
+        scene_ids,
+    };
+
+    let mut roots: Vec<_> = world
+        .scene_entities(scene)
+        .filter(|(_, id)| writer.parent(*id).is_none())
+        .collect();
+    roots.sort();
+
+    // Components are written as `Name(field: ...)`, as they are read.
+    let mut serializer =
+        ron::ser::Serializer::new(Some(PrettyConfig::default()), true);
+    SceneWriter {
+        writer: &writer,
+        entities: roots,
+    }
+    .serialize(&mut serializer)?;
+
+    Ok(serializer.into_output_string())
+}
+
+/// An entity of a scene file: its id, its components by name and the
+/// entities attached to it.
+///
+/// ```ron
+/// (
+///     id: 6,
+///     components: [
+///         Transform(position: [0., 0., 0.], ...),
+///         RigidBody(mass: 1.2, status: Kinematic),
+///         Player,
+///     ],
+///     children: [...],
+/// )
+/// ```
+///
+/// Components can come from a prefab file, the fields written in the
+/// overrides replace the ones of the prefab:
+///
+/// ```ron
+/// (
+///     id: 8,
+///     components: [
+///         Prefab(
+///             path: "crate.ron",
+///             overrides: [Transform(position: [2., 1., 0.])],
+///         ),
+///     ],
+/// )
+/// ```
+#[derive(Debug)]
+pub struct SceneEntity {
+    pub id: SceneId,
+    pub entity: Entity,
+    pub children: Vec<SceneEntity>,
+}
+
+impl SceneEntity {
+    /// Flatten the nested entities, each one with the id of its parent.
+    pub fn flatten(
+        self,
+        parent: Option<SceneId>,
+        flat: &mut Vec<(Entity, Option<SceneId>)>,
+    ) {
+        let id = self.id;
+        flat.push((self.entity, parent));
+
+        self.children
+            .into_iter()
+            .for_each(|child| child.flatten(Some(id), flat));
+    }
+}
+
+/// Components are looked up by name in the registry, prefabs by file
+/// name.
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    scene: &'a str,
+    registry: &'a ComponentRegistry,
+    prefabs: &'a Prefabs,
+    /// Entity being read, for errors.
+    entity: &'a Cell<Option<SceneId>>,
+}
+
+impl<'a> Context<'a> {
+    fn registration<E: de::Error>(
+        &self,
+        name: &str,
+    ) -> Result<&'a ComponentRegistration, E> {
+        self.registry.get(name).ok_or_else(|| {
+            de::Error::custom(format!("unknown component `{}`", name))
+        })
+    }
+}
+
+/// Reads a scene file, `(entities: [...])`.
+struct SceneSeed<'a>(Context<'a>);
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for SceneSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Scene", &["entities"], self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for SceneSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a scene")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entities = None;
+
+        while let Some(Identifier(key)) = map.next_key()? {
+            match key.as_str() {
+                "entities" => {
+                    entities = Some(map.next_value_seed(EntitiesSeed(self.0))?)
+                }
+                key => {
+                    return Err(de::Error::unknown_field(key, &["entities"]))
+                }
+            }
+        }
+
+        entities.ok_or_else(|| de::Error::missing_field("entities"))
+    }
+}
+
+/// Reads a prefab file, `(components: [...])`.
+struct PrefabSeed<'a>(Context<'a>);
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for PrefabSeed<'a> {
+    type Value = Entity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Entity, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(PREFAB, &["components"], self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for PrefabSeed<'a> {
+    type Value = Entity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a prefab")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Entity, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entity = Entity::new();
+
+        while let Some(Identifier(key)) = map.next_key()? {
+            match key.as_str() {
+                "components" => {
+                    map.next_value_seed(ComponentsSeed {
+                        context: self.0,
+                        prefab: None,
+                        entity: &mut entity,
+                    })?;
+                }
+                key => {
+                    return Err(de::Error::unknown_field(key, &["components"]))
+                }
+            }
+        }
+
+        Ok(entity)
+    }
+}
+
+struct EntitiesSeed<'a>(Context<'a>);
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for EntitiesSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for EntitiesSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entities = vec![];
+
+        while let Some(entity) = seq.next_element_seed(EntitySeed(self.0))? {
+            entities.push(entity);
+        }
+
+        Ok(entities)
+    }
+}
+
+const ENTITY_FIELDS: &[&str] = &["id", "components", "children"];
+
+struct EntitySeed<'a>(Context<'a>);
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for EntitySeed<'a> {
+    type Value = SceneEntity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Entity", ENTITY_FIELDS, self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for EntitySeed<'a> {
+    type Value = SceneEntity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an entity")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut entity = Entity::new();
+        let mut children = vec![];
+        self.0.entity.set(None);
+
+        while let Some(Identifier(key)) = map.next_key()? {
+            // Children may have been read since.
+            if id.is_some() {
+                self.0.entity.set(id);
+            }
+
+            match key.as_str() {
+                "id" => {
+                    id = Some(map.next_value()?);
+                    self.0.entity.set(id);
+                }
+                "components" => {
+                    map.next_value_seed(ComponentsSeed {
+                        context: self.0,
+                        prefab: None,
+                        entity: &mut entity,
+                    })?;
+                }
+                "children" => {
+                    children = map.next_value_seed(EntitiesSeed(self.0))?
+                }
+                key => {
+                    return Err(de::Error::unknown_field(key, ENTITY_FIELDS))
+                }
+            }
+        }
+
+        let id = id.ok_or_else(|| de::Error::missing_field("id"))?;
+
+        Ok(SceneEntity {
+            id,
+            entity: entity.with_scene_id(self.0.scene, id),
+            children,
+        })
+    }
+}
+
+/// Adds the listed components to the entity builder, returns their
+/// names.
+/// With a prefab, the listed components are its overrides.
+struct ComponentsSeed<'a, 'b> {
+    context: Context<'a>,
+    prefab: Option<&'a Prefab>,
+    entity: &'b mut Entity,
+}
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for ComponentsSeed<'a, '_> {
+    type Value = Vec<&'static str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for ComponentsSeed<'a, '_> {
+    type Value = Vec<&'static str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of components")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut names = vec![];
+
+        while let Some(name) = seq.next_element_seed(ComponentSeed {
+            context: self.context,
+            prefab: self.prefab,
+            entity: &mut *self.entity,
+        })? {
+            names.push(name);
+        }
+
+        Ok(names)
+    }
+}
+
+/// A component is written as an enum variant named after it:
+/// `Player` for unit structs, `Transform(position: ...)` otherwise.
+struct ComponentSeed<'a, 'b> {
+    context: Context<'a>,
+    prefab: Option<&'a Prefab>,
+    entity: &'b mut Entity,
+}
+
+impl<'de, 'a: 'de> DeserializeSeed<'de> for ComponentSeed<'a, '_> {
+    type Value = &'static str;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_enum("Component", &[], self)
+    }
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for ComponentSeed<'a, '_> {
+    type Value = &'static str;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a component")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (Identifier(name), variant) = data.variant()?;
+        let entity = mem::take(self.entity);
+
+        // Prefabs can't be nested.
+        if name == PREFAB && self.prefab.is_none() {
+            *self.entity = variant.struct_variant(
+                PREFAB_FIELDS,
+                PrefabVisitor {
+                    context: self.context,
+                    entity,
+                },
+            )?;
+
+            return Ok(PREFAB);
+        }
+
+        let registration = self.context.registration(&name)?;
+        let fields = self
+            .prefab
+            .and_then(|prefab| prefab.fields(&name))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+
+        *self.entity = if registration.fields().is_empty() {
+            variant.unit_variant()?;
+            registration.deserialize(().into_deserializer(), entity)?
+        } else {
+            variant.struct_variant(
+                registration.fields(),
+                FieldsVisitor {
+                    registration,
+                    fields,
+                    entity,
+                },
+            )?
+        };
+
+        Ok(registration.name())
+    }
+}
+
+/// Instantiates a prefab: `Prefab(path: "crate.ron", overrides: [...])`.
+struct PrefabVisitor<'a> {
+    context: Context<'a>,
+    entity: Entity,
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for PrefabVisitor<'a> {
+    type Value = Entity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a prefab")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Entity, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entity = self.entity;
+        let mut prefab = None;
+        let mut overridden = vec![];
+
+        while let Some(Identifier(key)) = map.next_key()? {
+            match key.as_str() {
+                "path" => {
+                    let path: String = map.next_value()?;
+                    let found =
+                        self.context.prefabs.get(&path).ok_or_else(|| {
+                            de::Error::custom(format!(
+                                "unknown prefab `{}`",
+                                path
+                            ))
+                        })?;
+                    prefab = Some((path, found));
+                }
+                "overrides" => {
+                    let (_, found) = prefab.as_ref().ok_or_else(|| {
+                        de::Error::custom(
+                            "`path` should come before `overrides`",
+                        )
+                    })?;
+                    overridden = map.next_value_seed(ComponentsSeed {
+                        context: self.context,
+                        prefab: Some(found),
+                        entity: &mut entity,
+                    })?;
+                }
+                key => {
+                    return Err(de::Error::unknown_field(key, PREFAB_FIELDS))
+                }
+            }
+        }
+
+        let (path, prefab) =
+            prefab.ok_or_else(|| de::Error::missing_field("path"))?;
+
+        // Components which aren't overridden are read from the prefab only.
+        for (name, fields) in prefab.components() {
+            if overridden.contains(&name) {
+                continue;
+            }
+
+            let registration = self.context.registration(name)?;
+            entity = if registration.fields().is_empty() {
+                registration.deserialize(().into_deserializer(), entity)?
+            } else {
+                let fields = PrefabFields::<A>::new(None, fields);
+                registration
+                    .deserialize(MapAccessDeserializer::new(fields), entity)?
+            };
+        }
+
+        Ok(entity.with(PrefabInstance(path)))
+    }
+}
+
+struct FieldsVisitor<'a> {
+    registration: &'a ComponentRegistration,
+    fields: &'a [(String, String)],
+    entity: Entity,
+}
+
+impl<'de, 'a: 'de> Visitor<'de> for FieldsVisitor<'a> {
+    type Value = Entity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the fields of {}", self.registration.name())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Entity, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let fields = PrefabFields::new(Some(map), self.fields);
+        self.registration
+            .deserialize(MapAccessDeserializer::new(fields), self.entity)
+    }
+}
+
+/// Fields of a component read from a scene, the ones which are left out
+/// are read from the prefab.
+struct PrefabFields<'a, A> {
+    overrides: Option<A>,
+    fields: &'a [(String, String)],
+    overridden: Vec<String>,
+    value: Option<&'a str>,
+}
+
+impl<'a, A> PrefabFields<'a, A> {
+    fn new(overrides: Option<A>, fields: &'a [(String, String)]) -> Self {
+        Self {
+            overrides,
+            fields,
+            overridden: vec![],
+            value: None,
+        }
+    }
+}
+
+impl<'de, 'a: 'de, A> MapAccess<'de> for PrefabFields<'a, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if let Some(overrides) = self.overrides.as_mut() {
+            match overrides.next_key()? {
+                Some(Identifier(key)) => {
+                    self.overridden.push(key.clone());
+                    return seed.deserialize(key.into_deserializer()).map(Some);
+                }
+                None => self.overrides = None,
+            }
+        }
+
+        let overridden = &self.overridden;
+        let next = self
+            .fields
+            .iter()
+            .position(|(key, _)| !overridden.contains(key));
+
+        match next {
+            Some(index) => {
+                let (key, value) = &self.fields[index];
+                self.fields = &self.fields[index + 1..];
+                self.value = Some(value);
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match (self.value.take(), self.overrides.as_mut()) {
+            (Some(value), _) => {
+                let mut deserializer = ron::de::Deserializer::from_str(value);
+                seed.deserialize(&mut deserializer)
+                    .map_err(de::Error::custom)
+            }
+            (None, Some(overrides)) => overrides.next_value_seed(seed),
+            (None, None) => Err(de::Error::custom("value without a field")),
+        }
+    }
+}
+
+/// Name of a field or a component, as written without quotes.
+struct Identifier(String);
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct IdentifierVisitor;
+
+        impl<'de> Visitor<'de> for IdentifierVisitor {
+            type Value = Identifier;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an identifier")
+            }
+
+            fn visit_str<E: de::Error>(
+                self,
+                name: &str,
+            ) -> Result<Identifier, E> {
+                Ok(Identifier(String::from(name)))
+            }
+        }
+
+        deserializer.deserialize_identifier(IdentifierVisitor)
+    }
+}
+
+struct Writer<'a> {
+    world: &'a World,
+    registry: &'a ComponentRegistry,
+    prefabs: &'a Prefabs,
+    scene_ids: HashMap<EntityId, SceneId>,
+}
+
+impl Writer<'_> {
+    /// Parent of the entity, if it comes from a scene file too.
+    fn parent(&self, id: EntityId) -> Option<SceneId> {
+        let parent = self.world.query::<&Parent>().get(id).map(|p| p.0)?;
+        self.scene_ids.get(&parent).cloned()
+    }
+
+    /// Prefab the entity is instantiated from, if it still exists.
+    fn prefab(&self, id: EntityId) -> Option<(String, &Prefab)> {
+        let path = self.world.query::<&PrefabInstance>().get(id)?.0.clone();
+        let prefab = self.prefabs.get(&path)?;
+        Some((path, prefab))
+    }
+
+    fn children(&self, id: EntityId) -> Vec<(SceneId, EntityId)> {
+        match self.world.query::<&Children>().get(id) {
+            Some(children) => children
+                .0
+                .iter()
+                .filter_map(|child| {
+                    self.scene_ids
+                        .get(child)
+                        .map(|scene_id| (*scene_id, *child))
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+struct SceneWriter<'a> {
+    writer: &'a Writer<'a>,
+    entities: Vec<(SceneId, EntityId)>,
+}
+
+impl Serialize for SceneWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Scene", 1)?;
+        state.serialize_field(
+            "entities",
+            &EntitiesWriter {
+                writer: self.writer,
+                entities: &self.entities,
+            },
+        )?;
+        state.end()
+    }
+}
+
+struct EntitiesWriter<'a> {
+    writer: &'a Writer<'a>,
+    entities: &'a [(SceneId, EntityId)],
+}
+
+impl Serialize for EntitiesWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entities.iter().map(|&(scene_id, id)| {
+            EntityWriter {
+                writer: self.writer,
+                scene_id,
+                id,
+            }
+        }))
+    }
+}
+
+struct EntityWriter<'a> {
+    writer: &'a Writer<'a>,
+    scene_id: SceneId,
+    id: EntityId,
+}
+
+impl Serialize for EntityWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let children = self.writer.children(self.id);
+        let components = ComponentsWriter {
+            writer: self.writer,
+            id: self.id,
+            prefab: None,
+        };
+
+        let len = if children.is_empty() { 2 } else { 3 };
+        let mut state = serializer.serialize_struct("Entity", len)?;
+        state.serialize_field("id", &self.scene_id)?;
+        state.serialize_field("components", &components)?;
+
+        if children.is_empty() {
+            state.skip_field("children")?;
+        } else {
+            state.serialize_field(
+                "children",
+                &EntitiesWriter {
+                    writer: self.writer,
+                    entities: &children,
+                },
+            )?;
+        }
+
+        state.end()
+    }
+}
+
+/// Registered components of the entity. With a prefab, only the ones
+/// which differ from it are written.
+struct ComponentsWriter<'a> {
+    writer: &'a Writer<'a>,
+    id: EntityId,
+    prefab: Option<&'a Prefab>,
+}
+
+impl Serialize for ComponentsWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let Writer {
+            world, registry, ..
+        } = self.writer;
+
+        if self.prefab.is_none() {
+            if let Some((path, prefab)) = self.writer.prefab(self.id) {
+                return serializer.collect_seq(iter::once(PrefabWriter {
+                    writer: self.writer,
+                    id: self.id,
+                    path,
+                    prefab,
+                }));
+            }
+        }
+
+        serializer.collect_seq(
+            registry
+                .iter()
+                .filter(|registration| registration.contains(world, self.id))
+                .filter(|registration| match self.prefab {
+                    Some(prefab) => {
+                        prefab.is_overridden(registration, world, self.id)
+                    }
+                    None => true,
+                })
+                .map(|registration| ComponentWriter {
+                    registration,
+                    world,
+                    id: self.id,
+                }),
+        )
+    }
+}
+
+struct PrefabWriter<'a> {
+    writer: &'a Writer<'a>,
+    id: EntityId,
+    path: String,
+    prefab: &'a Prefab,
+}
+
+impl Serialize for PrefabWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state =
+            serializer.serialize_struct_variant("Component", 0, PREFAB, 2)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field(
+            "overrides",
+            &ComponentsWriter {
+                writer: self.writer,
+                id: self.id,
+                prefab: Some(self.prefab),
+            },
+        )?;
+        state.end()
+    }
+}
+
+struct ComponentWriter<'a> {
+    registration: &'a ComponentRegistration,
+    world: &'a World,
+    id: EntityId,
+}
+
+impl Serialize for ComponentWriter<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.registration
+            .serialize(self.world, self.id, serializer)
+            .unwrap_or_else(|| {
+                Err(ser::Error::custom(format!(
+                    "missing component `{}`",
+                    self.registration.name()
+                )))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::SceneErrorKind;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Frozen;
+
+    const SCENE_NAME: &str = "level.ron";
+
+    const SCENE: &str = "
+        (
+            entities: [
+                (
+                    id: 1,
+                    components: [Position(x: 1., y: 2.), Frozen],
+                    children: [
+                        (id: 2, components: [Position(x: 0., y: 1.)]),
+                    ],
+                ),
+                (id: 3, components: []),
+            ],
+        )
+    ";
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::default();
+        registry.register::<Position>();
+        registry.register_default::<Frozen>();
+        registry
+    }
+
+    fn load(
+        source: &str,
+        registry: &ComponentRegistry,
+        prefabs: &Prefabs,
+    ) -> World {
+        let mut world = World::new();
+        let mut links = vec![];
+
+        for (entity, parent) in
+            parse_scene(SCENE_NAME, source, registry, prefabs).unwrap()
+        {
+            links.push((entity.scene_id().unwrap(), parent));
+            world.add_entity(entity);
+        }
+
+        link_scene(&mut world, SCENE_NAME, &links);
+        world
+    }
+
+    #[test]
+    fn should_load_nested_entities() {
+        let world = load(SCENE, &registry(), &Prefabs::default());
+        let parent = world.scene_entity(SCENE_NAME, 1).unwrap();
+        let child = world.scene_entity(SCENE_NAME, 2).unwrap();
+
+        assert_eq!(world.entities_len(), 3);
+        assert_eq!(world.query::<&Parent>().get(child), Some(&Parent(parent)));
+        assert!(world.query::<&Frozen>().get(parent).is_some());
+    }
+
+    #[test]
+    fn should_save_what_it_loads() {
+        let registry = registry();
+        let prefabs = Prefabs::default();
+        let mut world = load(SCENE, &registry, &prefabs);
+        let parent = world.scene_entity(SCENE_NAME, 1).unwrap();
+        world.query::<&mut Position>().get(parent).unwrap().x = 5.;
+        // Entities which don't come from this file are not saved.
+        world.add_entity(Entity::new().with(Frozen));
+        world.add_entity(Entity::from_file("other.ron", 1).with(Frozen));
+
+        let saved =
+            write_scene(&world, SCENE_NAME, &registry, &prefabs).unwrap();
+        let reloaded = load(&saved, &registry, &prefabs);
+
+        assert_eq!(
+            write_scene(&reloaded, SCENE_NAME, &registry, &prefabs).unwrap(),
+            saved
+        );
+        assert_eq!(reloaded.entities_len(), 3);
+
+        let parent = reloaded.scene_entity(SCENE_NAME, 1).unwrap();
+        let child = reloaded.scene_entity(SCENE_NAME, 2).unwrap();
+        let mut positions = reloaded.query::<&Position>();
+        assert_eq!(positions.get(parent), Some(&Position { x: 5., y: 2. }));
+        assert_eq!(positions.get(child), Some(&Position { x: 0., y: 1. }));
+        assert_eq!(
+            reloaded.query::<&Parent>().get(child),
+            Some(&Parent(parent))
+        );
+    }
+
+    const PREFAB_SCENE: &str = "
+        (
+            entities: [
+                (
+                    id: 1,
+                    components: [
+                        Prefab(path: \"block.ron\", overrides: [Position(y: 5.)]),
+                    ],
+                ),
+                (id: 2, components: [Prefab(path: \"block.ron\")]),
+            ],
+        )
+    ";
+
+    fn prefabs(source: &str, registry: &ComponentRegistry) -> Prefabs {
+        let mut prefabs = Prefabs::default();
+        prefabs.insert(
+            "block.ron",
+            parse_prefab("block.ron", source, registry).unwrap(),
+        );
+        prefabs
+    }
+
+    #[test]
+    fn should_instantiate_prefabs() {
+        let registry = registry();
+        let prefab = "(components: [Position(x: 1., y: 2.), Frozen])";
+        let world = load(PREFAB_SCENE, &registry, &prefabs(prefab, &registry));
+        let overridden = world.scene_entity(SCENE_NAME, 1).unwrap();
+        let instance = world.scene_entity(SCENE_NAME, 2).unwrap();
+
+        let mut positions = world.query::<&Position>();
+        assert_eq!(positions.get(overridden), Some(&Position { x: 1., y: 5. }));
+        assert_eq!(positions.get(instance), Some(&Position { x: 1., y: 2. }));
+        assert!(world.query::<&Frozen>().get(overridden).is_some());
+        assert_eq!(
+            world.query::<&PrefabInstance>().get(instance),
+            Some(&PrefabInstance(String::from("block.ron")))
+        );
+
+        // Changes of the prefab reach the fields which aren't overridden.
+        let prefab = "(components: [Position(x: 3., y: 4.)])";
+        let world = load(PREFAB_SCENE, &registry, &prefabs(prefab, &registry));
+        let overridden = world.scene_entity(SCENE_NAME, 1).unwrap();
+
+        let mut positions = world.query::<&Position>();
+        assert_eq!(positions.get(overridden), Some(&Position { x: 3., y: 5. }));
+        assert!(world.query::<&Frozen>().get(overridden).is_none());
+    }
+
+    #[test]
+    fn should_save_prefab_overrides() {
+        let registry = registry();
+        let prefab = "(components: [Position(x: 1., y: 2.), Frozen])";
+        let prefabs = prefabs(prefab, &registry);
+        let world = load(PREFAB_SCENE, &registry, &prefabs);
+
+        let saved =
+            write_scene(&world, SCENE_NAME, &registry, &prefabs).unwrap();
+        let reloaded = load(&saved, &registry, &prefabs);
+
+        assert_eq!(
+            write_scene(&reloaded, SCENE_NAME, &registry, &prefabs).unwrap(),
+            saved
+        );
+        // Components equal to the ones of the prefab are left out.
+        assert!(!saved.contains("Frozen"));
+        assert_eq!(saved.matches("Position").count(), 1);
+
+        let overridden = reloaded.scene_entity(SCENE_NAME, 1).unwrap();
+        assert_eq!(
+            reloaded.query::<&Position>().get(overridden),
+            Some(&Position { x: 1., y: 5. })
+        );
+    }
+
+    #[test]
+    fn should_report_where_errors_are() {
+        let registry = registry();
+        let prefabs = Prefabs::default();
+
+        let source = "(
+            entities: [
+                (id: 1, components: []),
+                (id: 2, components: [Positon(x: 1., y: 2.)]),
+            ],
+        )";
+        let err =
+            parse_scene(SCENE_NAME, source, &registry, &prefabs).unwrap_err();
+        assert_eq!(err.file, SCENE_NAME);
+        assert_eq!(err.entity, Some(2));
+        assert_eq!(err.position.map(|(line, _)| line), Some(4));
+        assert_eq!(
+            err.kind,
+            SceneErrorKind::Syntax(String::from("unknown component `Positon`"))
+        );
+
+        let source = "(
+            entities: [
+                (id: 1, components: [Frozen Frozen]),
+            ],
+        )";
+        let err =
+            parse_scene(SCENE_NAME, source, &registry, &prefabs).unwrap_err();
+        assert_eq!(err.entity, Some(1));
+        assert_eq!(err.position.map(|(line, _)| line), Some(3));
+    }
+}
@@ -0,0 +1,600 @@
+use super::{
+    link_scene, parse_prefab, parse_scene, write_scene, PrefabInstance,
+    Prefabs, SceneError, SceneErrorKind, SceneStream,
+};
+use crate::{
+    asset_manager::AssetManager,
+    components::{
+        remove_parent, Camera, Collider, ColliderDesc, GlobalTransform, Light,
+        Mesh, MeshDesc, RigidBody, RigidBodyDesc, Transform,
+    },
+    constants::{PREFAB_PATH, SCENE_PATH, SHADER_PATH, TEXTURE_PATH},
+    ecs::{Commands, ComponentRegistry, Entity, EntityId, SceneId},
+    ecs::{Events, ReaderId, World},
+    events::{EditorEvent, SceneEvent, SceneRequest},
+    resources::{DebugInfo, PhysicWorld},
+    shader::Shader,
+    vfs::Vfs,
+};
+use nalgebra_glm as glm;
+use nphysics3d::math::Isometry;
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::{io, mem};
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// Entities read from a scene file, each one with the scene id of its
+/// parent if it is nested.
+pub type SceneEntities = Vec<(Entity, Option<SceneId>)>;
+
+/// A scene loader.
+/// Scenes are loaded additively, each entity remembers the scene it comes
+/// from so a scene can be unloaded with all of its entities.
+/// A loaded scene is loaded again when its file, or a prefab it uses,
+/// changes on disk. Textures and shaders are reloaded by the asset manager.
+/// Scenes asked through `SceneRequest` are streamed in the background.
+pub struct SceneLoader {
+    /// Loaded scenes, in the order they were loaded.
+    scenes: Vec<String>,
+    streams: Vec<SceneStream>,
+    receiver: Receiver<DebouncedEvent>,
+    editor_events: ReaderId<EditorEvent>,
+    scene_requests: ReaderId<SceneRequest>,
+    #[allow(unused)]
+    watcher: RecommendedWatcher,
+}
+
+impl SceneLoader {
+    /// Scenes and prefabs are watched in the directories of the file system
+    /// mounted from the disk.
+    pub fn new(delay: u64, vfs: &Vfs) -> notify::Result<Self> {
+        let (sender, receiver) = channel();
+        let mut watcher: RecommendedWatcher =
+            Watcher::new(sender, Duration::from_secs(delay))?;
+
+        for dir in &[SCENE_PATH, PREFAB_PATH] {
+            for path in vfs.directories(dir) {
+                watcher.watch(path, RecursiveMode::NonRecursive)?;
+            }
+        }
+
+        Ok(Self {
+            watcher,
+            receiver,
+            editor_events: ReaderId::default(),
+            scene_requests: ReaderId::default(),
+            scenes: vec![],
+            streams: vec![],
+        })
+    }
+
+    pub fn watch(&mut self, world: &mut World) {
+        let changes: Vec<PathBuf> = self
+            .receiver
+            .try_iter()
+            .filter_map(|event| match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => Some(path),
+                _ => None,
+            })
+            .collect();
+
+        let mut reload = HashSet::new();
+        for path in changes {
+            reload.extend(self.on_change(world, &path));
+        }
+
+        // Reloaded in the order they were loaded.
+        let reload: Vec<String> = self
+            .scenes
+            .iter()
+            .filter(|scene| reload.contains(*scene))
+            .cloned()
+            .collect();
+        for scene in reload {
+            self.reload(world, &scene);
+        }
+
+        let scene_requests: Vec<SceneRequest> = world
+            .resource::<Events<SceneRequest>>()
+            .read(&mut self.scene_requests)
+            .cloned()
+            .collect();
+
+        for request in scene_requests {
+            match request {
+                SceneRequest::Load(scene) => self.stream(world, &scene),
+                SceneRequest::Unload(scene) => self.unload(world, &scene),
+                SceneRequest::Transition { from, to } => {
+                    self.transition(world, &from, &to)
+                }
+            }
+        }
+        self.step_streams(world);
+
+        let requests: Vec<EditorEvent> = world
+            .resource::<Events<EditorEvent>>()
+            .read(&mut self.editor_events)
+            .copied()
+            .collect();
+
+        if requests.contains(&EditorEvent::ValidateRequested) {
+            let errors = self.validate(world);
+            errors
+                .iter()
+                .for_each(|err| eprintln!("Invalid scene: {}", err));
+            world.resource_mut::<DebugInfo>().scene_error =
+                errors.first().map(|err| err.to_string());
+        }
+
+        if requests.contains(&EditorEvent::SaveRequested) {
+            for scene in &self.scenes {
+                match Self::save(world, scene) {
+                    Ok(()) => world
+                        .resource_mut::<Events<SceneEvent>>()
+                        .send(SceneEvent::Saved(scene.clone())),
+                    Err(err) => eprintln!("Could not save {}: {}", scene, err),
+                }
+            }
+        }
+    }
+
+    /// Loaded scenes which should be loaded again.
+    fn on_change(&self, world: &World, path: &Path) -> Vec<String> {
+        let (dir, file) = match (
+            path.parent(),
+            path.file_name().and_then(|file| file.to_str()),
+        ) {
+            (Some(dir), Some(file)) => (dir, file),
+            _ => return vec![],
+        };
+
+        if dir.ends_with(SCENE_PATH) {
+            return self
+                .scenes
+                .iter()
+                .filter(|scene| scene.as_str() == file)
+                .cloned()
+                .collect();
+        }
+
+        if !dir.ends_with(PREFAB_PATH) {
+            return vec![];
+        }
+
+        // Only the scenes holding an instance of the prefab, they are
+        // patched by the reload.
+        let mut instances = world.query::<&PrefabInstance>();
+        instances
+            .iter()
+            .filter(|(_, prefab)| prefab.0 == file)
+            .filter_map(|(id, _)| world.entity_scene(id))
+            .map(|(scene, _)| String::from(scene))
+            .collect()
+    }
+
+    /// Load a scene along with the ones already loaded.
+    pub fn load(&mut self, world: &mut World, scene: &str) {
+        if self.scenes.iter().any(|loaded| loaded == scene) {
+            return self.reload(world, scene);
+        }
+
+        if let Some(entities) = Self::read_checked(world, scene) {
+            self.add_scene(world, scene, entities);
+        }
+    }
+
+    /// Apply the entities of a scene read beforehand, the scene is loaded
+    /// again if it already was.
+    fn add_scene(
+        &mut self,
+        world: &mut World,
+        scene: &str,
+        entities: SceneEntities,
+    ) {
+        let event = if self.scenes.iter().any(|loaded| loaded == scene) {
+            SceneEvent::Reloaded
+        } else {
+            self.scenes.push(String::from(scene));
+            SceneEvent::Loaded
+        };
+
+        Self::apply(world, scene, entities);
+        Self::loaded(world, event(String::from(scene)));
+    }
+
+    /// Until the file is fixed, the previous version of the scene is kept.
+    fn reload(&mut self, world: &mut World, scene: &str) {
+        if let Some(entities) = Self::read_checked(world, scene) {
+            Self::apply(world, scene, entities);
+            Self::loaded(world, SceneEvent::Reloaded(String::from(scene)));
+        }
+    }
+
+    /// Despawn every entity the scene spawned.
+    pub fn unload(&mut self, world: &mut World, scene: &str) {
+        let ids: Vec<EntityId> =
+            world.scene_entities(scene).map(|(_, id)| id).collect();
+
+        for id in ids {
+            remove_parent(world, id);
+            world.despawn(id);
+        }
+        world.release_removed();
+
+        self.scenes.retain(|loaded| loaded != scene);
+        world
+            .resource_mut::<Events<SceneEvent>>()
+            .send(SceneEvent::Unloaded(String::from(scene)));
+    }
+
+    /// Load a scene in the background, along with the ones already loaded.
+    /// The file is read and checked by a `SceneStream`, which prepares its
+    /// textures and meshes too. A scene already streaming isn't streamed
+    /// twice.
+    pub fn stream(&mut self, world: &World, scene: &str) {
+        if self.streams.iter().any(|stream| stream.scene() == scene) {
+            return;
+        }
+
+        self.streams.push(Self::start_stream(world, scene));
+    }
+
+    /// Replace a scene by another one, streamed in the background.
+    /// `from` is only unloaded once `to` is ready, so a broken level
+    /// doesn't leave the world empty.
+    pub fn transition(&mut self, world: &World, from: &str, to: &str) {
+        let stream = Self::start_stream(world, to);
+        self.streams.push(stream.replacing(from));
+    }
+
+    fn start_stream(world: &World, scene: &str) -> SceneStream {
+        let vfs = world.resource::<AssetManager>().shared_vfs();
+        let registry = world.resource::<ComponentRegistry>().clone();
+
+        SceneStream::new(scene, vfs, registry)
+    }
+
+    /// Upload a few assets of each stream, and apply the ready ones.
+    fn step_streams(&mut self, world: &mut World) {
+        for mut stream in mem::take(&mut self.streams) {
+            let step = stream.step(&mut world.resource_mut::<AssetManager>());
+
+            match step {
+                Ok(false) => self.streams.push(stream),
+                Ok(true) => {
+                    let (scene, replaces, entities) = stream.finish();
+                    if let Some(replaces) = replaces {
+                        self.unload(world, &replaces);
+                    }
+                    self.add_scene(world, &scene, entities);
+                }
+                Err(err) => Self::failed(world, err),
+            }
+        }
+
+        // Shared by every stream, for a loading screen.
+        let progress = self.streams.iter().map(SceneStream::progress);
+        world.resource_mut::<DebugInfo>().loading = match self.streams.len() {
+            0 => None,
+            count => Some(progress.sum::<f32>() / count as f32),
+        };
+    }
+
+    /// Read the scene and check its assets, errors are reported to the
+    /// HUD and as a `SceneEvent::Failed`.
+    fn read_checked(world: &World, scene: &str) -> Option<SceneEntities> {
+        let asset_manager = world.resource::<AssetManager>();
+        let registry = world.resource::<ComponentRegistry>();
+        let entities = Self::read_scene(asset_manager.vfs(), scene, &registry)
+            .and_then(|entities| {
+                let errors =
+                    Self::check_assets(scene, &entities, &asset_manager);
+                errors.into_iter().next().map_or(Ok(entities), Err)
+            });
+
+        match entities {
+            Ok(entities) => Some(entities),
+            Err(err) => {
+                Self::failed(world, err);
+                None
+            }
+        }
+    }
+
+    fn failed(world: &World, err: SceneError) {
+        eprintln!("Could not load the scene: {}", err);
+        world.resource_mut::<DebugInfo>().scene_error = Some(err.to_string());
+        world
+            .resource_mut::<Events<SceneEvent>>()
+            .send(SceneEvent::Failed(err));
+    }
+
+    fn loaded(world: &World, event: SceneEvent) {
+        world.resource_mut::<DebugInfo>().scene_error = None;
+        world.resource_mut::<Events<SceneEvent>>().send(event);
+    }
+
+    /// Errors of the loaded scene files: syntax errors, or textures and
+    /// shaders their meshes use which can't be found.
+    pub fn validate(&self, world: &World) -> Vec<SceneError> {
+        let registry = world.resource::<ComponentRegistry>();
+        let asset_manager = world.resource::<AssetManager>();
+
+        self.scenes
+            .iter()
+            .flat_map(|scene| {
+                match Self::read_scene(asset_manager.vfs(), scene, &registry) {
+                    Ok(entities) => {
+                        Self::check_assets(scene, &entities, &asset_manager)
+                    }
+                    Err(err) => vec![err],
+                }
+            })
+            .collect()
+    }
+
+    /// Textures are looked up in the texture directory, shaders are the
+    /// programs of the asset manager or the sources of the shader
+    /// directory.
+    pub(super) fn check_assets(
+        scene: &str,
+        entities: &[(Entity, Option<SceneId>)],
+        asset_manager: &AssetManager,
+    ) -> Vec<SceneError> {
+        let exists = |dir: &str, file: &str| {
+            asset_manager.vfs().exists(&[dir, file].join(""))
+        };
+        let mut errors = vec![];
+
+        for (entity, _) in entities {
+            let mesh = match entity.get::<MeshDesc>() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let error = |kind| {
+                let err = SceneError::new(kind).in_file(scene);
+                match entity.scene_id() {
+                    Some(id) => err.with_entity(id),
+                    None => err,
+                }
+            };
+
+            if let Some(texture) = mesh.texture.as_ref() {
+                if !exists(TEXTURE_PATH, texture) {
+                    errors.push(error(SceneErrorKind::MissingTexture(
+                        texture.clone(),
+                    )));
+                }
+            }
+
+            let shader = &mesh.shader;
+            let sources = exists(SHADER_PATH, &format!("{}.vert", shader))
+                && exists(SHADER_PATH, &format!("{}.frag", shader));
+            if !asset_manager.contains::<Shader>(shader) && !sources {
+                errors
+                    .push(error(SceneErrorKind::MissingShader(shader.clone())));
+            }
+        }
+
+        errors
+    }
+
+    /// Returns the entities of the scene, each one with the scene id of
+    /// its parent if it is nested.
+    pub fn read_scene(
+        vfs: &Vfs,
+        scene: &str,
+        registry: &ComponentRegistry,
+    ) -> Result<SceneEntities, SceneError> {
+        let path = [SCENE_PATH, scene].join("");
+        let source = vfs.read_to_string(&path).map_err(|err| {
+            SceneError::new(SceneErrorKind::Io(err.to_string())).in_file(scene)
+        })?;
+        let prefabs = Self::read_prefabs(vfs, registry)?;
+
+        parse_scene(scene, &source, registry, &prefabs)
+    }
+
+    /// Returns the prefabs of the prefab directory, by file name.
+    pub fn read_prefabs(
+        vfs: &Vfs,
+        registry: &ComponentRegistry,
+    ) -> Result<Prefabs, SceneError> {
+        let mut prefabs = Prefabs::default();
+
+        let files = vfs.read_dir(PREFAB_PATH);
+
+        for name in files.iter().filter(|name| name.ends_with(".ron")) {
+            let path = [PREFAB_PATH, name].join("");
+            let source = vfs.read_to_string(&path).map_err(|err| {
+                SceneError::new(SceneErrorKind::Io(err.to_string()))
+                    .in_file(name)
+            })?;
+            let prefab = parse_prefab(name, &source, registry)?;
+
+            prefabs.insert(name, prefab);
+        }
+
+        Ok(prefabs)
+    }
+
+    /// Bring the world up to date with the entities of a scene: the ones
+    /// removed from the file are despawned, the others are patched in
+    /// place, and only the new ones are spawned.
+    fn apply(world: &mut World, scene: &str, entities: SceneEntities) {
+        let ids: HashSet<SceneId> = entities
+            .iter()
+            .filter_map(|(entity, _)| entity.scene_id())
+            .collect();
+
+        let removed: Vec<EntityId> = world
+            .scene_entities(scene)
+            .filter(|(scene_id, _)| !ids.contains(scene_id))
+            .map(|(_, id)| id)
+            .collect();
+
+        for id in removed {
+            remove_parent(world, id);
+            world.despawn(id);
+        }
+
+        let mut links = vec![];
+
+        for (mut entity, parent) in entities {
+            let scene_id = match entity.scene_id() {
+                Some(scene_id) => scene_id,
+                None => continue,
+            };
+            links.push((scene_id, parent));
+
+            let id = match world.scene_entity(scene, scene_id) {
+                // The camera is moved by the editor, it keeps its state.
+                Some(id) if world.query::<&Camera>().get(id).is_some() => {
+                    continue
+                }
+                Some(id) => id,
+                None => world.add_entity(
+                    Entity::from_file(scene, scene_id)
+                        .with(GlobalTransform::default()),
+                ),
+            };
+
+            let ubo_index = world
+                .query::<&Light>()
+                .get(id)
+                .map(|light| light.ubo_index());
+
+            // Meshes of streamed scenes were already sent to the GPU.
+            let mesh = entity.take::<Mesh>();
+
+            let mut commands = Commands::new();
+            let changed = world.resource::<ComponentRegistry>().patch(
+                world,
+                id,
+                &mut entity,
+                &mut commands,
+            );
+            commands.apply(world);
+
+            match entity.take::<PrefabInstance>() {
+                Some(prefab) => world.insert(id, prefab),
+                None => world.remove::<PrefabInstance>(id),
+            };
+
+            if changed.contains(&TypeId::of::<Light>()) {
+                if let Some(mut light) = world.query::<&mut Light>().get(id) {
+                    match ubo_index {
+                        Some(ubo_index) => light.set_ubo_index(ubo_index),
+                        None => light.set_ubo(),
+                    }
+                }
+            }
+
+            Self::build(world, id, &changed, mesh);
+        }
+
+        link_scene(world, scene, &links);
+        world.release_removed();
+    }
+
+    /// Write the entities loaded from a scene file back to it, with their
+    /// registered components as they are now.
+    pub fn save(world: &World, scene: &str) -> io::Result<()> {
+        let invalid =
+            |err: String| io::Error::new(io::ErrorKind::InvalidData, err);
+        let registry = world.resource::<ComponentRegistry>();
+        let asset_manager = world.resource::<AssetManager>();
+        let vfs = asset_manager.vfs();
+        let prefabs = Self::read_prefabs(vfs, &registry)
+            .map_err(|err| invalid(err.to_string()))?;
+        let source = write_scene(world, scene, &registry, &prefabs)
+            .map_err(|err| invalid(err.to_string()))?;
+
+        vfs.write(&[SCENE_PATH, scene].join(""), source.as_bytes())
+    }
+
+    /// Build again the components living outside of the world (GPU
+    /// meshes, physic bodies...) whose description changed.
+    /// `mesh` is used instead of building one, if given.
+    fn build(
+        world: &mut World,
+        id: EntityId,
+        changed: &[TypeId],
+        mut mesh: Option<Mesh>,
+    ) {
+        let changed = |ty| changed.contains(&ty);
+
+        if changed(TypeId::of::<MeshDesc>()) {
+            let built = world.query::<&MeshDesc>().get(id).map(|desc| {
+                mesh.take().unwrap_or_else(|| {
+                    desc.build(&mut world.resource_mut::<AssetManager>())
+                })
+            });
+
+            match built {
+                Some(built) => world.insert(id, built),
+                None => world.remove::<Mesh>(id),
+            };
+        }
+        if let Some(mesh) = mesh {
+            mesh.release();
+        }
+
+        let position = world
+            .query::<&Transform>()
+            .get(id)
+            .map(|transform| transform.position)
+            .unwrap_or_else(glm::zero);
+
+        let body = world.query::<&RigidBodyDesc>().get(id).cloned();
+        let collider = world.query::<&ColliderDesc>().get(id).cloned();
+
+        let rebuild = changed(TypeId::of::<RigidBodyDesc>())
+            || changed(TypeId::of::<ColliderDesc>())
+            // A collider standing alone can't be moved.
+            || (changed(TypeId::of::<Transform>()) && body.is_none());
+
+        if rebuild {
+            world.remove::<RigidBody>(id);
+            world.remove::<Collider>(id);
+
+            let mut physic_world = world.resource_mut::<PhysicWorld>();
+            let rigid_body = body.map(|body| {
+                body.build(&mut physic_world, position, collider.as_ref())
+            });
+
+            // Without a body to attach it to, the collider stands alone.
+            let collider = match rigid_body {
+                Some(_) => None,
+                None => collider.map(|collider| {
+                    collider.build(&mut physic_world, position)
+                }),
+            };
+            drop(physic_world);
+
+            if let Some(rigid_body) = rigid_body {
+                world.insert(id, rigid_body);
+            }
+            if let Some(collider) = collider {
+                world.insert(id, collider);
+            }
+        } else if changed(TypeId::of::<Transform>()) {
+            // The body is kept, only moved to its new position.
+            if let Some(rigid_body) = world.query::<&RigidBody>().get(id) {
+                let mut physic_world = world.resource_mut::<PhysicWorld>();
+                let body = rigid_body.get_mut(&mut physic_world);
+
+                body.set_position(Isometry::translation(
+                    position.x, position.y, position.z,
+                ));
+                body.activate();
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod error;
+mod format;
+mod loader;
+mod prefab;
+mod stream;
+
+pub use error::*;
+pub use format::*;
+pub use loader::*;
+pub use prefab::*;
+pub use stream::*;
@@ -0,0 +1,215 @@
+use crate::ecs::{
+    ComponentRegistration, ComponentRegistry, Entity, EntityId, World,
+};
+use serde::ser::{self, Impossible, Serialize, SerializeStruct, Serializer};
+use std::collections::HashMap;
+
+/// Fields of a component, each one with its value written in RON.
+pub type Fields = Vec<(String, String)>;
+
+/// Components of a prefab file, by name.
+///
+/// ```ron
+/// (
+///     components: [
+///         Mesh(primitive: Cube, texture: Some("crate.png"), shader: "cube"),
+///         RigidBody(mass: 1.2, status: Dynamic),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Prefab {
+    components: Vec<(&'static str, Fields)>,
+}
+
+impl Prefab {
+    /// Read back the registered components of the entity builder.
+    pub fn new(
+        entity: Entity,
+        registry: &ComponentRegistry,
+    ) -> Result<Self, ron::ser::Error> {
+        let mut world = World::new();
+        let id = world.add_entity(entity);
+
+        let components = registry
+            .iter()
+            .filter_map(|registration| {
+                registration
+                    .serialize(&world, id, FieldsSerializer)
+                    .map(|fields| Ok((registration.name(), fields?)))
+            })
+            .collect::<Result<_, ron::ser::Error>>()?;
+
+        Ok(Self { components })
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = (&str, &Fields)> + '_ {
+        self.components.iter().map(|(name, fields)| (*name, fields))
+    }
+
+    /// Whether the component of the entity differs from the one of the
+    /// prefab.
+    pub fn is_overridden(
+        &self,
+        registration: &ComponentRegistration,
+        world: &World,
+        id: EntityId,
+    ) -> bool {
+        let fields = registration.serialize(world, id, FieldsSerializer);
+
+        match (fields, self.fields(registration.name())) {
+            (Some(Ok(fields)), Some(prefab)) => fields != *prefab,
+            _ => true,
+        }
+    }
+
+    /// Fields of the component in the prefab, if it has one.
+    pub fn fields(&self, name: &str) -> Option<&Fields> {
+        self.components
+            .iter()
+            .find(|(component, _)| *component == name)
+            .map(|(_, fields)| fields)
+    }
+}
+
+/// Prefabs by file name.
+#[derive(Debug, Default)]
+pub struct Prefabs(HashMap<String, Prefab>);
+
+impl Prefabs {
+    pub fn insert(&mut self, name: &str, prefab: Prefab) {
+        self.0.insert(String::from(name), prefab);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.0.get(name)
+    }
+}
+
+/// Marks an entity instantiated from a prefab, with the prefab file name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefabInstance(pub String);
+
+/// Writes each field of a struct in RON, on its own.
+struct FieldsSerializer;
+
+struct FieldsWriter(Fields);
+
+impl SerializeStruct for FieldsWriter {
+    type Ok = Fields;
+    type Error = ron::ser::Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0
+            .push((String::from(key), ron::ser::to_string(&value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Fields, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+macro_rules! not_a_struct {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+                Err(ser::Error::custom("a component should be a struct"))
+            }
+        )*
+    };
+}
+
+impl Serializer for FieldsSerializer {
+    type Ok = Fields;
+    type Error = ron::ser::Error;
+    type SerializeSeq = Impossible<Fields, Self::Error>;
+    type SerializeTuple = Impossible<Fields, Self::Error>;
+    type SerializeTupleStruct = Impossible<Fields, Self::Error>;
+    type SerializeTupleVariant = Impossible<Fields, Self::Error>;
+    type SerializeMap = Impossible<Fields, Self::Error>;
+    type SerializeStruct = FieldsWriter;
+    type SerializeStructVariant = Impossible<Fields, Self::Error>;
+
+    fn serialize_unit_struct(
+        self,
+        _: &'static str,
+    ) -> Result<Fields, Self::Error> {
+        Ok(vec![])
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<FieldsWriter, Self::Error> {
+        Ok(FieldsWriter(Vec::with_capacity(len)))
+    }
+
+    fn serialize_some<T>(self, _: &T) -> Result<Fields, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(ser::Error::custom("a component should be a struct"))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Fields, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(ser::Error::custom("a component should be a struct"))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Fields, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(ser::Error::custom("a component should be a struct"))
+    }
+
+    not_a_struct! {
+        serialize_bool(bool) -> Fields;
+        serialize_i8(i8) -> Fields;
+        serialize_i16(i16) -> Fields;
+        serialize_i32(i32) -> Fields;
+        serialize_i64(i64) -> Fields;
+        serialize_u8(u8) -> Fields;
+        serialize_u16(u16) -> Fields;
+        serialize_u32(u32) -> Fields;
+        serialize_u64(u64) -> Fields;
+        serialize_f32(f32) -> Fields;
+        serialize_f64(f64) -> Fields;
+        serialize_char(char) -> Fields;
+        serialize_str(&str) -> Fields;
+        serialize_bytes(&[u8]) -> Fields;
+        serialize_none() -> Fields;
+        serialize_unit() -> Fields;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Fields;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize)
+            -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+}
@@ -0,0 +1,230 @@
+use super::{SceneEntities, SceneError, SceneErrorKind, SceneLoader};
+use crate::{
+    asset_manager::{AssetManager, Handle, LoadState, Texture},
+    components::{Mesh, MeshData, MeshDesc},
+    ecs::ComponentRegistry,
+    vfs::Vfs,
+};
+use std::mem;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// Meshes sent to the GPU each frame by a stream, so a frame never waits
+/// for a whole scene.
+const UPLOADS_PER_FRAME: usize = 2;
+
+/// Sent by the worker of a stream.
+enum Message {
+    /// Entities of the scene file, with their prefabs resolved.
+    Read(Result<SceneEntities, SceneError>),
+    /// Vertices of the mesh of an entity, by index in the scene.
+    Prepared(usize, MeshData),
+}
+
+/// A scene loaded in the background.
+/// A worker thread reads the scene file and builds its meshes on the CPU,
+/// meanwhile its textures are queued in the asset manager, then `step`
+/// sends a few meshes to the GPU each frame.
+pub struct SceneStream {
+    scene: String,
+    /// Scene unloaded once this one is ready.
+    replaces: Option<String>,
+    read: bool,
+    entities: SceneEntities,
+    // Keep the textures loaded until the meshes using them are built.
+    textures: Vec<(String, Handle<Texture>)>,
+    receiver: Receiver<Message>,
+    loaded: usize,
+    uploaded: usize,
+    meshes: usize,
+}
+
+impl SceneStream {
+    /// Start reading the scene and preparing its meshes in the background.
+    pub fn new(
+        scene: &str,
+        vfs: Arc<Vfs>,
+        registry: ComponentRegistry,
+    ) -> Self {
+        let (sender, receiver) = channel();
+        let name = String::from(scene);
+
+        // Sending fails once the stream is dropped, nothing is left to do.
+        thread::spawn(move || {
+            let read = SceneLoader::read_scene(&vfs, &name, &registry);
+            let meshes: Vec<_> = match &read {
+                Ok(entities) => entities
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, (entity, _))| {
+                        Some((index, entity.get::<MeshDesc>()?.primitive))
+                    })
+                    .collect(),
+                Err(_) => vec![],
+            };
+
+            if sender.send(Message::Read(read)).is_err() {
+                return;
+            }
+            for (index, primitive) in meshes {
+                let data = MeshData::new(primitive);
+                if sender.send(Message::Prepared(index, data)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            scene: String::from(scene),
+            replaces: None,
+            read: false,
+            entities: vec![],
+            textures: vec![],
+            receiver,
+            loaded: 0,
+            uploaded: 0,
+            meshes: 0,
+        }
+    }
+
+    /// Unload `scene` once the stream is ready.
+    pub fn replacing(mut self, scene: &str) -> Self {
+        self.replaces = Some(String::from(scene));
+        self
+    }
+
+    pub fn scene(&self) -> &str {
+        &self.scene
+    }
+
+    /// Share of the textures loaded and meshes sent to the GPU, from 0
+    /// to 1.
+    pub fn progress(&self) -> f32 {
+        if !self.read {
+            return 0.;
+        }
+
+        let total = self.textures.len() + self.meshes;
+        if total == 0 {
+            return 1.;
+        }
+
+        (self.loaded + self.uploaded) as f32 / total as f32
+    }
+
+    /// Send the meshes prepared so far to the GPU, a few at a time.
+    /// Returns whether the scene is ready to be applied, errors of the
+    /// scene file and of its assets are returned once read.
+    pub fn step(
+        &mut self,
+        asset_manager: &mut AssetManager,
+    ) -> Result<bool, SceneError> {
+        for _ in 0..UPLOADS_PER_FRAME {
+            match self.receiver.try_recv() {
+                Ok(Message::Read(entities)) => {
+                    self.start(entities?, asset_manager)?
+                }
+                Ok(Message::Prepared(index, data)) => {
+                    self.upload(index, data, asset_manager)
+                }
+                Err(TryRecvError::Empty) => break,
+                // The thread panicked.
+                Err(TryRecvError::Disconnected) => {
+                    let failed = if !self.read {
+                        Some("the scene couldn't be read")
+                    } else if self.uploaded < self.meshes {
+                        Some("the meshes couldn't be prepared")
+                    } else {
+                        None
+                    };
+                    if let Some(failed) = failed {
+                        let kind = SceneErrorKind::Io(String::from(failed));
+                        return Err(SceneError::new(kind).in_file(&self.scene));
+                    }
+                    break;
+                }
+            }
+        }
+
+        self.loaded = 0;
+        for (name, texture) in &self.textures {
+            match asset_manager.load_state(texture) {
+                Some(LoadState::Loaded) => self.loaded += 1,
+                Some(LoadState::Failed(err)) => {
+                    let kind = SceneErrorKind::Io(format!("{}: {}", name, err));
+                    return Err(SceneError::new(kind).in_file(&self.scene));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(self.read
+            && self.loaded == self.textures.len()
+            && self.uploaded == self.meshes)
+    }
+
+    // Check the assets of the entities read by the worker, and queue their
+    // textures. Textures the asset manager already has aren't read again.
+    fn start(
+        &mut self,
+        entities: SceneEntities,
+        asset_manager: &mut AssetManager,
+    ) -> Result<(), SceneError> {
+        let errors =
+            SceneLoader::check_assets(&self.scene, &entities, asset_manager);
+        if let Some(err) = errors.into_iter().next() {
+            return Err(err);
+        }
+
+        for (entity, _) in &entities {
+            let mesh = match entity.get::<MeshDesc>() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            self.meshes += 1;
+
+            if let Some(texture) = mesh.texture.as_ref() {
+                if self.textures.iter().all(|(name, _)| name != texture) {
+                    let handle = asset_manager.load::<Texture>(texture);
+                    self.textures.push((texture.clone(), handle));
+                }
+            }
+        }
+
+        self.entities = entities;
+        self.read = true;
+        Ok(())
+    }
+
+    // The mesh is added to the entity builder, the loader uses it instead of
+    // building one.
+    fn upload(
+        &mut self,
+        index: usize,
+        data: MeshData,
+        asset_manager: &mut AssetManager,
+    ) {
+        let (entity, _) = &mut self.entities[index];
+        let mesh = entity.get::<MeshDesc>().map(|desc| {
+            let texture = desc
+                .texture
+                .as_ref()
+                .and_then(|texture| asset_manager.handle(texture));
+            let shader = desc.load_shader(asset_manager);
+
+            Mesh::from_data(&data, texture, shader)
+        });
+
+        if let Some(mesh) = mesh {
+            *entity = mem::take(entity).with(mesh);
+        }
+
+        self.uploaded += 1;
+    }
+
+    /// Name of the scene, the scene it replaces, and its entities.
+    pub fn finish(self) -> (String, Option<String>, SceneEntities) {
+        (self.scene, self.replaces, self.entities)
+    }
+}
@@ -1,13 +1,16 @@
 use gl;
 use gl::types::*;
 use std::ffi::CString;
-use std::fs::File;
-use std::io::Read;
-use std::path::PathBuf;
 use std::ptr;
-use std::str;
 
-use crate::constants::SHADER_PATH;
+use crate::{
+    constants::{CAMERA_UBO_BINDING, LIGHTS_UBO_BINDING, SHADER_PATH},
+    opengl::OpenGL,
+    vfs::Vfs,
+};
+
+/// Size of the logs read from the driver.
+const LOG_CAPACITY: usize = 1024;
 
 #[derive(Default, Debug)]
 pub struct Shader {
@@ -26,33 +29,83 @@ impl Shader {
         }
     }
 
-    pub fn with_vert(self, shader_name: &str) -> Self {
-        let fragment = format!("{}.vert", shader_name);
-        let shader = self.compile_shader(gl::VERTEX_SHADER, &fragment);
-
-        unsafe {
-            gl::AttachShader(self.id, shader);
-            gl::LinkProgram(self.id);
-            gl::DeleteShader(shader);
-        }
-
+    pub fn with_vert(mut self, vfs: &Vfs, shader_name: &str) -> Self {
+        self.vert_name = String::from(shader_name);
+        let vertex = format!("{}.vert", shader_name);
+        self.attach(vfs, gl::VERTEX_SHADER, &vertex);
         self
     }
 
-    pub fn with_frag(self, shader_name: &str) -> Self {
+    pub fn with_frag(mut self, vfs: &Vfs, shader_name: &str) -> Self {
+        self.frag_name = String::from(shader_name);
         let fragment = format!("{}.frag", shader_name);
-        let shader = self.compile_shader(gl::FRAGMENT_SHADER, &fragment);
+        self.attach(vfs, gl::FRAGMENT_SHADER, &fragment);
+        self
+    }
+
+    fn attach(&self, vfs: &Vfs, shader_type: GLenum, file_path: &str) {
+        let shader = match self.compile_shader(vfs, shader_type, file_path) {
+            Ok(shader) => shader,
+            Err(err) => return eprintln!("{}", err),
+        };
 
         unsafe {
             gl::AttachShader(self.id, shader);
             gl::LinkProgram(self.id);
             gl::DeleteShader(shader);
         }
+    }
 
-        self
+    /// Build a new program from the sources of the shader directory.
+    /// Nothing is kept if a source doesn't compile or the program doesn't
+    /// link.
+    pub fn compile(vfs: &Vfs, vert: &str, frag: &str) -> Result<Self, String> {
+        let mut program = Self::new();
+        program.vert_name = String::from(vert);
+        program.frag_name = String::from(frag);
+
+        let sources = [
+            (gl::VERTEX_SHADER, format!("{}.vert", vert)),
+            (gl::FRAGMENT_SHADER, format!("{}.frag", frag)),
+        ];
+        for (shader_type, file_path) in sources.iter() {
+            match program.compile_shader(vfs, *shader_type, file_path) {
+                Ok(shader) => unsafe {
+                    gl::AttachShader(program.id, shader);
+                    gl::DeleteShader(shader);
+                },
+                Err(err) => {
+                    program.delete_program();
+                    return Err(err);
+                }
+            }
+        }
+
+        match unsafe { program.link() } {
+            Ok(()) => Ok(program),
+            Err(err) => {
+                program.delete_program();
+                Err(err)
+            }
+        }
+    }
+
+    /// Whether the program is built from a source with this name.
+    pub fn uses(&self, shader_name: &str) -> bool {
+        self.vert_name == shader_name || self.frag_name == shader_name
+    }
+
+    /// Names of the vertex and fragment sources.
+    pub fn sources(&self) -> (&str, &str) {
+        (&self.vert_name, &self.frag_name)
+    }
+
+    /// Bind the camera and lights uniform blocks, see `RenderContext`.
+    pub fn bind_uniform_blocks(&self) {
+        OpenGL::set_uniform_block(self.id, CAMERA_UBO_BINDING, "Camera");
+        OpenGL::set_uniform_block(self.id, LIGHTS_UBO_BINDING, "Lights");
     }
 
-    #[allow(unused)]
     pub fn delete_program(&self) {
         unsafe {
             gl::DeleteProgram(self.id);
@@ -102,21 +155,19 @@ impl Shader {
         unsafe { gl::GetUniformLocation(self.id, var_name.as_ptr()) }
     }
 
-    fn compile_shader(&self, shader_type: GLenum, file_path: &str) -> u32 {
-        let mut base = PathBuf::from(SHADER_PATH);
-
-        base.push(file_path);
-        let mut shader_file = File::open(base).unwrap();
-        let mut shader_string = String::new();
-
-        // Transform file to string and store it in a variable
-        shader_file
-            .read_to_string(&mut shader_string)
-            .expect("Failed to read vertex shader file");
+    fn compile_shader(
+        &self,
+        vfs: &Vfs,
+        shader_type: GLenum,
+        file_path: &str,
+    ) -> Result<u32, String> {
+        let shader_string = vfs
+            .read_to_string(&[SHADER_PATH, file_path].join(""))
+            .map_err(|err| format!("{}: {}", file_path, err))?;
 
         // convert to C compatible string
-        let shader_source_string =
-            CString::new(shader_string.as_bytes()).unwrap();
+        let shader_source_string = CString::new(shader_string.as_bytes())
+            .map_err(|err| format!("{}: {}", file_path, err))?;
 
         unsafe {
             let shader = gl::CreateShader(shader_type);
@@ -128,31 +179,62 @@ impl Shader {
                 ptr::null(),
             );
             gl::CompileShader(shader);
-            self.check_shader_compile_error(shader);
 
-            shader
+            match self.check_shader_compile_error(shader) {
+                Ok(()) => Ok(shader),
+                Err(log) => {
+                    gl::DeleteShader(shader);
+                    Err(format!("{}: {}", file_path, log))
+                }
+            }
         }
     }
 
-    unsafe fn check_shader_compile_error(&self, shader: GLuint) {
-        const CAPACITY: usize = 1024;
+    unsafe fn check_shader_compile_error(
+        &self,
+        shader: GLuint,
+    ) -> Result<(), String> {
         let mut success = i32::from(gl::FALSE);
-        let mut info_log = Vec::with_capacity(CAPACITY);
-        info_log.set_len(CAPACITY - 1);
-
         gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
 
-        if success != i32::from(gl::TRUE) {
-            gl::GetShaderInfoLog(
-                shader,
-                CAPACITY as i32,
-                ptr::null_mut(),
-                info_log.as_mut_ptr() as *mut GLchar,
-            );
-            eprintln!(
-                "ERROR::SHADER::VERTEX::COMPILATION_FAILED\n{}",
-                str::from_utf8(&info_log).unwrap()
-            );
+        if success == i32::from(gl::TRUE) {
+            return Ok(());
         }
+
+        let mut info_log = vec![0; LOG_CAPACITY];
+        gl::GetShaderInfoLog(
+            shader,
+            LOG_CAPACITY as i32,
+            ptr::null_mut(),
+            info_log.as_mut_ptr() as *mut GLchar,
+        );
+        Err(Self::read_log(&info_log))
+    }
+
+    unsafe fn link(&self) -> Result<(), String> {
+        gl::LinkProgram(self.id);
+
+        let mut success = i32::from(gl::FALSE);
+        gl::GetProgramiv(self.id, gl::LINK_STATUS, &mut success);
+
+        if success == i32::from(gl::TRUE) {
+            return Ok(());
+        }
+
+        let mut info_log = vec![0; LOG_CAPACITY];
+        gl::GetProgramInfoLog(
+            self.id,
+            LOG_CAPACITY as i32,
+            ptr::null_mut(),
+            info_log.as_mut_ptr() as *mut GLchar,
+        );
+        Err(Self::read_log(&info_log))
+    }
+
+    // The log ends with a nul character.
+    fn read_log(info_log: &[u8]) -> String {
+        let end = info_log.iter().position(|c| *c == 0);
+        let log = &info_log[..end.unwrap_or(info_log.len())];
+        String::from_utf8_lossy(log).trim().to_string()
     }
 }
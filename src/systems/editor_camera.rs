@@ -1,81 +1,97 @@
 use crate::window::KeyEvents;
-use crate::GameState;
 use crate::{
     components::{Camera, Transform},
-    ecs::{Entity, System, World},
+    ecs::{Commands, NonSend, NonSendMut, Query, Res, System},
+    editor::Editor,
     opengl::OpenGL,
+    resources::{DebugInfo, RenderContext},
     time::Time,
     window::Window,
 };
 use glutin::{MouseButton, VirtualKeyCode};
 use nalgebra_glm as glm;
-use std::any::TypeId;
 
 #[derive(Debug, Default)]
 pub struct EditorCamera;
 
-impl System for EditorCamera {
-    fn get_targets(&self) -> Vec<TypeId> {
-        vec![World::get_type::<Transform>(), World::get_type::<Camera>()]
-    }
+impl<'a> System<'a> for EditorCamera {
+    type Data = (
+        Res<'a, Editor>,
+        NonSendMut<'a, Window>,
+        Res<'a, Time>,
+        NonSend<'a, RenderContext>,
+        NonSendMut<'a, DebugInfo>,
+        Query<'a, (&'a mut Transform, &'a mut Camera)>,
+    );
+
+    fn run(&mut self, data: Self::Data, _commands: &mut Commands) {
+        let (editor, mut window, time, context, mut debug_info, mut query) =
+            data;
 
-    fn process(&self, entity: &mut Entity, state: &mut GameState) {
         // TODO: wtf!?
-        if !state.editor_mode {
+        if !editor.enabled {
             return;
         }
 
-        let mut window = &state.window;
-        let time = &state.time;
-        let mouse = window.get_mouse_events();
-
-        // We want to hide the cursor when we move our camera.
-        mouse.pressed(MouseButton::Right, || {
-            window.hide_cursor(true);
-
-            if mouse.has_moved {
-                update_spin(entity, &mut window, &time);
-            }
-        });
-
-        mouse.trigger_on_release(MouseButton::Right, || {
-            window.hide_cursor(false);
-        });
-
-        update_pos(entity, &mut state.window.get_keyboard_events(), &time);
-
-        let cam = entity.get::<Camera>();
-        let transform = entity.get::<Transform>();
-
-        let cam_pos = format!(
-            "({:.1},{:.1},{:.1})",
-            transform.position.x, transform.position.y, transform.position.z
-        );
-        state.cam_pos = cam_pos;
-
-        let view = glm::look_at(
-            &transform.position,
-            &(transform.position + cam.front),
-            &cam.up,
-        );
-
-        OpenGL::set_mat4_to_ubo(view, state.camera_ubo, 64);
-        OpenGL::set_vec3_to_ubo(transform.position, state.camera_ubo, 192);
-
-        let center = glm::vec3(0., 0., 0.);
-        let sk = glm::look_at(&center, &(center + cam.front), &cam.up);
-        OpenGL::set_mat4_to_ubo(sk, state.camera_ubo, 128);
+        for (_, (mut transform, mut cam)) in query.iter() {
+            let mouse = window.get_mouse_events();
+
+            // We want to hide the cursor when we move our camera.
+            mouse.pressed(MouseButton::Right, || {
+                window.hide_cursor(true);
+
+                if mouse.has_moved {
+                    update_spin(&mut cam, &window, &time);
+                }
+            });
+
+            mouse.trigger_on_release(MouseButton::Right, || {
+                window.hide_cursor(false);
+            });
+
+            update_pos(
+                &mut transform,
+                &cam,
+                window.get_keyboard_events(),
+                &time,
+            );
+
+            let cam_pos = format!(
+                "({:.1},{:.1},{:.1})",
+                transform.position.x,
+                transform.position.y,
+                transform.position.z
+            );
+            debug_info.cam_pos = cam_pos;
+
+            let view = glm::look_at(
+                &transform.position,
+                &(transform.position + cam.front),
+                &cam.up,
+            );
+
+            OpenGL::set_mat4_to_ubo(view, context.camera_ubo, 64);
+            OpenGL::set_vec3_to_ubo(
+                transform.position,
+                context.camera_ubo,
+                192,
+            );
+
+            let center = glm::vec3(0., 0., 0.);
+            let sk = glm::look_at(&center, &(center + cam.front), &cam.up);
+            OpenGL::set_mat4_to_ubo(sk, context.camera_ubo, 128);
+        }
     }
 }
 
-fn update_pos(entity: &mut Entity, keyboard: &mut KeyEvents, time: &Time) {
-    let (speed, front, up) = {
-        let cam = entity.get::<Camera>();
-        (cam.speed, cam.front, cam.up)
-    };
-    let transform = entity.get_mut::<Transform>();
-
-    let mut speed = (speed * time.dt) as f32;
+fn update_pos(
+    transform: &mut Transform,
+    cam: &Camera,
+    keyboard: &mut KeyEvents,
+    time: &Time,
+) {
+    let (front, up) = (cam.front, cam.up);
+    let mut speed = (cam.speed * time.dt) as f32;
 
     if keyboard.modifiers.shift {
         speed *= 2.5;
@@ -109,8 +125,7 @@ fn update_pos(entity: &mut Entity, keyboard: &mut KeyEvents, time: &Time) {
 }
 
 // We are using the delta time for smoother spin.
-fn update_spin(entity: &mut Entity, window: &Window, time: &Time) {
-    let cam = entity.get_mut::<Camera>();
+fn update_spin(cam: &mut Camera, window: &Window, time: &Time) {
     let mouse_event = window.get_mouse_events();
 
     let (delta_x, delta_y) = mouse_event.delta;
@@ -1,9 +1,13 @@
 mod editor_camera;
 mod physic;
+mod physic_step;
 mod player;
 mod renderer;
+mod transform_propagation;
 
 pub use editor_camera::EditorCamera;
 pub use physic::Physic;
+pub use physic_step::PhysicStep;
 pub use player::Player;
 pub use renderer::Renderer;
+pub use transform_propagation::TransformPropagation;
@@ -0,0 +1,70 @@
+use crate::{
+    components::{Collider, RigidBody},
+    ecs::{Commands, EntityId, EventWriter, NonSendMut, Query, System},
+    events::Contact,
+    resources::PhysicWorld,
+};
+use ncollide3d::events::ContactEvent;
+use std::collections::HashMap;
+
+/// Advance the physic world, run at a fixed timestep.
+/// Contacts between entities are published as `Contact` events.
+#[derive(Debug, Default)]
+pub struct PhysicStep;
+
+impl<'a> System<'a> for PhysicStep {
+    type Data = (
+        NonSendMut<'a, PhysicWorld>,
+        EventWriter<'a, Contact>,
+        Query<'a, &'a Collider>,
+        Query<'a, &'a RigidBody>,
+    );
+
+    fn run(&mut self, data: Self::Data, _commands: &mut Commands) {
+        let (mut physic_world, mut contacts, mut colliders, mut bodies) = data;
+
+        physic_world.step();
+
+        let events: Vec<ContactEvent> =
+            physic_world.contact_events().iter().cloned().collect();
+
+        if events.is_empty() {
+            return;
+        }
+
+        // Static colliders are components, the other ones are owned by
+        // the rigid body of the entity.
+        let colliders: HashMap<_, EntityId> = colliders
+            .iter()
+            .map(|(entity, collider)| (collider.handle(), entity))
+            .collect();
+        let bodies: HashMap<_, EntityId> = bodies
+            .iter()
+            .map(|(entity, body)| (body.handle(), entity))
+            .collect();
+
+        let entity = |handle| {
+            colliders.get(&handle).cloned().or_else(|| {
+                physic_world
+                    .collider(handle)
+                    .and_then(|collider| bodies.get(&collider.body()))
+                    .cloned()
+            })
+        };
+
+        for event in events {
+            let contact = match event {
+                ContactEvent::Started(a, b) => match (entity(a), entity(b)) {
+                    (Some(a), Some(b)) => Contact::Started(a, b),
+                    _ => continue,
+                },
+                ContactEvent::Stopped(a, b) => match (entity(a), entity(b)) {
+                    (Some(a), Some(b)) => Contact::Stopped(a, b),
+                    _ => continue,
+                },
+            };
+
+            contacts.send(contact);
+        }
+    }
+}
@@ -1,32 +1,29 @@
-use crate::GameState;
 use crate::{
     components::{Player as PlayerComponent, RigidBody, Transform},
-    ecs::{Entity, System, World},
+    ecs::{Commands, NonSendMut, Query, Res, System, With},
+    resources::PhysicWorld,
+    time::Time,
+    window::Window,
 };
 use glutin::VirtualKeyCode;
 use nalgebra as na;
 use nalgebra_glm as glm;
 use na::{geometry::Translation};
-use std::any::TypeId;
 
 #[derive(Debug, Default)]
 pub struct Player;
 
-impl System for Player {
-    fn get_targets(&self) -> Vec<TypeId> {
-        vec![
-            World::get_type::<Transform>(),
-            World::get_type::<RigidBody>(),
-            World::get_type::<PlayerComponent>(),
-        ]
-    }
-
-    fn process(&self, entity: &mut Entity, state: &mut GameState) {
-        let rigid = entity.get_mut::<RigidBody>();
-        let body = rigid.get_mut(&mut state.physic_world);
+impl<'a> System<'a> for Player {
+    type Data = (
+        Res<'a, Time>,
+        NonSendMut<'a, Window>,
+        NonSendMut<'a, PhysicWorld>,
+        Query<'a, &'a RigidBody, (With<Transform>, With<PlayerComponent>)>,
+    );
 
-        let time = &state.time;
-        let keyboard = state.window.get_keyboard_events();
+    fn run(&mut self, data: Self::Data, _commands: &mut Commands) {
+        let (time, mut window, mut physic_world, mut query) = data;
+        let keyboard = window.get_keyboard_events();
 
         let speed = 0.8 * time.dt as f32;
         let mut vector = glm::vec3(0., 0., 0.);
@@ -54,8 +51,12 @@ impl System for Player {
 
         let translation = Translation::from(vector);
 
-        let mut position = body.position().clone();
-        position.append_translation_mut(&translation);
-        body.set_position(position);
+        for (_, rigid) in query.iter() {
+            let body = rigid.get_mut(&mut physic_world);
+
+            let mut position = body.position().clone();
+            position.append_translation_mut(&translation);
+            body.set_position(position);
+        }
     }
 }
@@ -1,64 +1,63 @@
-use crate::GameState;
 use crate::{
-    components::{Light, Mesh, Transform},
-    ecs::{Entity, System, World},
+    asset_manager::AssetManager,
+    components::{GlobalTransform, Light, Mesh},
+    ecs::{Changed, Commands, NonSend, Or, Query, System},
     opengl::OpenGL,
-    shader::Shader,
+    resources::RenderContext,
 };
 use nalgebra_glm as glm;
-use std::any::TypeId;
 
 #[derive(Debug, Default)]
 pub struct Renderer;
 
-impl System for Renderer {
-    fn get_targets(&self) -> Vec<TypeId> {
-        vec![World::get_type::<Mesh>(), World::get_type::<Transform>()]
-    }
-
-    fn process(&self, entity: &mut Entity, state: &mut GameState) {
-        let transform = entity.get::<Transform>();
-        let mesh = entity.get::<Mesh>();
-        let light = entity.get_opt::<Light>();
-
-        let mut model = glm::Mat4::identity();
-
-        model = glm::translate(&model, &transform.position);
-
-        if let Some((axis, angle)) = transform.rotation.axis_angle() {
-            model = glm::rotate(&model, angle, &axis);
-        }
-
-        model = glm::scale(&model, &transform.scale);
-
-        let vao = mesh.get_vao();
-        let shader = state.asset_manager.get_ressource::<Shader>(mesh.shader);
-        let texture_key = mesh.get_texture();
-
-        OpenGL::use_shader(shader.id);
-
-        shader.set_matrix4("model", glm::value_ptr(&model));
-
-        if let Some(light) = light {
-            light.set_to_shader(state.lights_ubo, &transform);
-        }
-
-        if texture_key.is_some() {
-            shader.set_int("material.diffuse", 0);
-        }
-        shader.set_vec3("material.specular", &(0.5, 0.5, 0.5));
-        shader.set_float("material.shininess", 32.);
-        shader.set_vec3("color", &mesh.color);
-
-        let mut texture = None;
-        if let Some(texture_key) = texture_key {
-            texture = state.asset_manager.get_asset(texture_key.as_str()).gl_id;
+impl<'a> System<'a> for Renderer {
+    type Data = (
+        NonSend<'a, AssetManager>,
+        NonSend<'a, RenderContext>,
+        Query<
+            'a,
+            (&'a GlobalTransform, &'a Light),
+            Or<(Changed<GlobalTransform>, Changed<Light>)>,
+        >,
+        Query<'a, (&'a GlobalTransform, &'a Mesh)>,
+    );
+
+    fn run(&mut self, data: Self::Data, _commands: &mut Commands) {
+        let (asset_manager, context, mut lights, mut meshes) = data;
+
+        for (_, (transform, light)) in lights.iter() {
+            light.set_to_shader(context.lights_ubo, transform);
         }
 
-        if mesh.has_ebo {
-            OpenGL::draw_with_ebo(vao, texture, mesh.lines);
-        } else {
-            OpenGL::draw(vao, texture, mesh.lines);
+        for (_, (transform, mesh)) in meshes.iter() {
+            let vao = mesh.get_vao();
+            let shader = match asset_manager.get(&mesh.shader) {
+                Some(shader) => shader,
+                None => continue,
+            };
+
+            OpenGL::use_shader(shader.id);
+
+            shader.set_matrix4("model", glm::value_ptr(&transform.0));
+
+            if mesh.texture.is_some() {
+                shader.set_int("material.diffuse", 0);
+            }
+            shader.set_vec3("material.specular", &(0.5, 0.5, 0.5));
+            shader.set_float("material.shininess", 32.);
+            shader.set_vec3("color", &mesh.color);
+
+            let texture = mesh
+                .texture
+                .as_ref()
+                .and_then(|texture| asset_manager.get(texture))
+                .and_then(|texture| texture.gl_id);
+
+            if mesh.has_ebo {
+                OpenGL::draw_with_ebo(vao, texture, mesh.lines);
+            } else {
+                OpenGL::draw(vao, texture, mesh.lines);
+            }
         }
     }
 }
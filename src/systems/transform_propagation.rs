@@ -0,0 +1,68 @@
+use crate::{
+    components::{Children, GlobalTransform, Parent, Transform},
+    ecs::{Commands, EntityId, Mut, Query, System, With, Without},
+};
+use nalgebra_glm as glm;
+
+type Node<'a> = (&'a Transform, &'a mut GlobalTransform, Option<&'a Children>);
+
+/// Compute the `GlobalTransform` of each entity from its `Transform` and
+/// the ones of its parents.
+#[derive(Debug, Default)]
+pub struct TransformPropagation;
+
+impl<'a> System<'a> for TransformPropagation {
+    type Data = (
+        Query<'a, &'a Transform, Without<GlobalTransform>>,
+        Query<'a, Node<'a>, Without<Parent>>,
+        Query<'a, Node<'a>, With<Parent>>,
+    );
+
+    fn run(&mut self, data: Self::Data, commands: &mut Commands) {
+        let (mut missing, mut roots, mut nested) = data;
+
+        // Spawned entities are updated on the next run.
+        for (id, transform) in missing.iter() {
+            commands.insert(id, GlobalTransform::from(transform));
+        }
+
+        for (_, (transform, mut global, children)) in roots.iter() {
+            let matrix = transform.matrix();
+            update(&mut global, matrix);
+
+            if let Some(children) = children {
+                propagate(&matrix, &children.0, &mut nested);
+            }
+        }
+    }
+}
+
+fn propagate(
+    parent: &glm::Mat4,
+    children: &[EntityId],
+    query: &mut Query<Node, With<Parent>>,
+) {
+    for child in children {
+        let (matrix, children) = match query.get(*child) {
+            Some((transform, mut global, children)) => {
+                let matrix = parent * transform.matrix();
+                update(&mut global, matrix);
+
+                (matrix, children.map(|children| children.0.clone()))
+            }
+            None => continue,
+        };
+
+        if let Some(children) = children {
+            propagate(&matrix, &children, query);
+        }
+    }
+}
+
+// Only write when the matrix moved, so unchanged entities aren't marked
+// changed.
+fn update(global: &mut Mut<GlobalTransform>, matrix: glm::Mat4) {
+    if global.0 != matrix {
+        global.0 = matrix;
+    }
+}
@@ -0,0 +1,5 @@
+mod mount;
+mod pack;
+
+pub use mount::*;
+pub use pack::*;
@@ -0,0 +1,263 @@
+use super::Pack;
+use crate::constants::{ASSETS_PACK, ASSETS_PATH};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+enum Mount {
+    Directory(PathBuf),
+    Pack(Pack),
+}
+
+/// Files of the game, read through mount points instead of paths relative
+/// to the working directory.
+/// A mount point is a directory, a pack file or a pack embedded in the
+/// executable. Paths use `/` separators, and a file of the last mount
+/// shadows the ones of the same path mounted before.
+#[derive(Default)]
+pub struct Vfs {
+    // Each mount with the prefix of the paths it serves, like `textures/`,
+    // or empty for all of them.
+    mounts: Vec<(String, Mount)>,
+}
+
+impl Vfs {
+    /// The assets of the game: the pack next to the executable for release
+    /// builds, or else the asset directory of the working directory, of the
+    /// executable, or of the sources.
+    pub fn assets() -> io::Result<Self> {
+        let mut vfs = Self::default();
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.parent()?.to_path_buf()));
+
+        if let Some(pack) = exe_dir.as_ref().map(|dir| dir.join(ASSETS_PACK)) {
+            if pack.is_file() {
+                vfs.mount_pack("", &pack)?;
+                return Ok(vfs);
+            }
+        }
+
+        let dirs = [
+            Some(PathBuf::from(ASSETS_PATH)),
+            exe_dir.map(|dir| dir.join(ASSETS_PATH)),
+            Some(Path::new(env!("CARGO_MANIFEST_DIR")).join(ASSETS_PATH)),
+        ];
+        match dirs.iter().flatten().find(|dir| dir.is_dir()) {
+            Some(dir) => vfs.mount_dir("", dir),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no asset directory or pack",
+                ))
+            }
+        }
+
+        Ok(vfs)
+    }
+
+    pub fn mount_dir(&mut self, point: &str, dir: &Path) {
+        self.mounts
+            .push((String::from(point), Mount::Directory(dir.to_path_buf())));
+    }
+
+    pub fn mount_pack(&mut self, point: &str, path: &Path) -> io::Result<()> {
+        let pack = Pack::open(path)?;
+        self.mounts.push((String::from(point), Mount::Pack(pack)));
+        Ok(())
+    }
+
+    /// Mount a pack embedded with `include_bytes!`.
+    #[allow(unused)]
+    pub fn mount_bytes(
+        &mut self,
+        point: &str,
+        bytes: &'static [u8],
+    ) -> io::Result<()> {
+        let pack = Pack::from_bytes(bytes)?;
+        self.mounts.push((String::from(point), Mount::Pack(pack)));
+        Ok(())
+    }
+
+    /// Mounts serving the path, the last one first, with the path relative
+    /// to each of them.
+    fn resolve<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> impl Iterator<Item = (&'a Mount, &'a str)> {
+        self.mounts.iter().rev().filter_map(move |(point, mount)| {
+            Some((mount, path.strip_prefix(point.as_str())?))
+        })
+    }
+
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        for (mount, relative) in self.resolve(path) {
+            match mount {
+                Mount::Directory(dir) => {
+                    let file = dir.join(relative);
+                    if file.is_file() {
+                        return fs::read(file);
+                    }
+                }
+                Mount::Pack(pack) if pack.contains(relative) => {
+                    return pack.read(relative);
+                }
+                Mount::Pack(_) => {}
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+
+    pub fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.resolve(path).any(|(mount, relative)| match mount {
+            Mount::Directory(dir) => dir.join(relative).is_file(),
+            Mount::Pack(pack) => pack.contains(relative),
+        })
+    }
+
+    /// Names of the files of a directory, like `prefabs/`, in every mount.
+    pub fn read_dir(&self, dir: &str) -> Vec<String> {
+        let mut names = BTreeSet::new();
+
+        for (mount, relative) in self.resolve(dir) {
+            match mount {
+                Mount::Directory(root) => {
+                    let files = match fs::read_dir(root.join(relative)) {
+                        Ok(files) => files,
+                        Err(_) => continue,
+                    };
+                    names.extend(
+                        files
+                            .filter_map(|file| Some(file.ok()?.path()))
+                            .filter(|path| path.is_file())
+                            .filter_map(|path| {
+                                Some(path.file_name()?.to_str()?.to_string())
+                            }),
+                    );
+                }
+                Mount::Pack(pack) => names.extend(
+                    pack.names()
+                        .filter_map(|name| name.strip_prefix(relative))
+                        .filter(|name| !name.is_empty() && !name.contains('/'))
+                        .map(String::from),
+                ),
+            }
+        }
+
+        names.into_iter().collect()
+    }
+
+    /// Write a file in the last mounted directory serving its path, packs
+    /// are read only.
+    pub fn write(&self, path: &str, content: &[u8]) -> io::Result<()> {
+        let file =
+            self.resolve(path)
+                .find_map(|(mount, relative)| match mount {
+                    Mount::Directory(dir) => Some(dir.join(relative)),
+                    Mount::Pack(_) => None,
+                });
+
+        match file {
+            Some(file) => fs::write(file, content),
+            None => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{}: no directory mounted to write in", path),
+            )),
+        }
+    }
+
+    /// Directories on disk serving a directory of the file system, like
+    /// `textures/`, to watch them.
+    pub fn directories(&self, dir: &str) -> Vec<PathBuf> {
+        self.resolve(dir)
+            .filter_map(|(mount, relative)| match mount {
+                Mount::Directory(root) => Some(root.join(relative)),
+                Mount::Pack(_) => None,
+            })
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_pack;
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("engine-vfs-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("prefabs")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_read_last_mount_first() {
+        let dir = temp_dir("shadow");
+        fs::write(dir.join("prefabs/a.ron"), "dir").unwrap();
+        fs::write(dir.join("prefabs/b.ron"), "dir").unwrap();
+
+        let mut vfs = Vfs::default();
+        vfs.mount_bytes(
+            "",
+            test_pack(&[("prefabs/a.ron", "pack"), ("c", "c")]),
+        )
+        .unwrap();
+        vfs.mount_dir("", &dir);
+        vfs.mount_bytes("prefabs/", test_pack(&[("b.ron", "override")]))
+            .unwrap();
+
+        assert_eq!(vfs.read_to_string("prefabs/a.ron").unwrap(), "dir");
+        assert_eq!(vfs.read_to_string("prefabs/b.ron").unwrap(), "override");
+        assert_eq!(vfs.read_to_string("c").unwrap(), "c");
+        assert!(vfs.exists("c"));
+        assert!(!vfs.exists("prefabs/c"));
+        assert_eq!(vfs.read("d").unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn should_list_files_of_every_mount() {
+        let dir = temp_dir("list");
+        fs::write(dir.join("prefabs/a.ron"), "").unwrap();
+
+        let mut vfs = Vfs::default();
+        vfs.mount_dir("", &dir);
+        vfs.mount_bytes(
+            "",
+            test_pack(&[
+                ("prefabs/b.ron", ""),
+                ("prefabs/c/d.ron", ""),
+                ("e", ""),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(vfs.read_dir("prefabs/"), vec!["a.ron", "b.ron"]);
+        assert_eq!(vfs.directories("prefabs/"), vec![dir.join("prefabs/")]);
+    }
+
+    #[test]
+    fn should_write_in_directories_only() {
+        let dir = temp_dir("write");
+
+        let mut vfs = Vfs::default();
+        vfs.mount_bytes("", test_pack(&[("prefabs/a.ron", "pack")]))
+            .unwrap();
+        assert_eq!(
+            vfs.write("prefabs/a.ron", b"new").unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+
+        vfs.mount_dir("", &dir);
+        vfs.write("prefabs/a.ron", b"new").unwrap();
+        assert_eq!(vfs.read_to_string("prefabs/a.ron").unwrap(), "new");
+    }
+}
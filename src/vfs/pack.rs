@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// First bytes of a pack file.
+const MAGIC: &[u8; 4] = b"EPAK";
+const VERSION: u32 = 1;
+/// Longest file name of a pack, in bytes.
+const MAX_NAME_LEN: usize = 4096;
+
+/// Where the files of a pack are read from.
+enum Source {
+    File(PathBuf),
+    Bytes(&'static [u8]),
+}
+
+/// Files bundled in one archive.
+/// It starts with an index of the files, by path with `/` separators,
+/// followed by their content:
+///
+/// `EPAK` version count, then for each file: name length, name, offset,
+/// size. Numbers are little endian, u64 for offsets and sizes, u32 for the
+/// others.
+pub struct Pack {
+    // Offset from the start of the pack and size of each file.
+    index: HashMap<String, (u64, u64)>,
+    source: Source,
+}
+
+impl Pack {
+    /// Read the index of a pack file, its files are read when asked for.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let index = read_index(&mut file, len)?;
+
+        Ok(Self {
+            index,
+            source: Source::File(path.to_path_buf()),
+        })
+    }
+
+    /// A pack embedded in the executable.
+    pub fn from_bytes(bytes: &'static [u8]) -> io::Result<Self> {
+        let index =
+            read_index(&mut io::Cursor::new(bytes), bytes.len() as u64)?;
+
+        Ok(Self {
+            index,
+            source: Source::Bytes(bytes),
+        })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    pub fn read(&self, name: &str) -> io::Result<Vec<u8>> {
+        let (offset, size) = *self.index.get(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, name.to_string())
+        })?;
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| invalid("file out of the pack"))?;
+
+        match &self.source {
+            Source::File(path) => {
+                let mut file = File::open(path)?;
+                // The pack may have changed since its index was read.
+                if end > file.metadata()?.len() {
+                    return Err(invalid("file out of the pack"));
+                }
+
+                let mut content = vec![0; size as usize];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut content)?;
+                Ok(content)
+            }
+            Source::Bytes(bytes) if end <= bytes.len() as u64 => {
+                Ok(bytes[offset as usize..end as usize].to_vec())
+            }
+            Source::Bytes(_) => Err(invalid("file out of the pack")),
+        }
+    }
+}
+
+fn invalid(err: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Read the index of a pack of `len` bytes, every file must be in it.
+fn read_index(
+    reader: &mut impl Read,
+    len: u64,
+) -> io::Result<HashMap<String, (u64, u64)>> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a pack file"));
+    }
+    if read_u32(reader)? != VERSION {
+        return Err(invalid("unsupported pack version"));
+    }
+
+    // Each entry takes at least its name length, offset and size.
+    let count = read_u32(reader)?;
+    if u64::from(count) * (4 + 8 + 8) > len {
+        return Err(invalid("too many files for the pack size"));
+    }
+    let mut index = HashMap::new();
+
+    for _ in 0..count {
+        let name_len = read_u32(reader)? as usize;
+        if name_len > MAX_NAME_LEN {
+            return Err(invalid("file name too long"));
+        }
+
+        let mut name = vec![0; name_len];
+        reader.read_exact(&mut name)?;
+        let name = String::from_utf8(name)
+            .map_err(|_| invalid("file name isn't valid utf-8"))?;
+
+        let (offset, size) = (read_u64(reader)?, read_u64(reader)?);
+        match offset.checked_add(size) {
+            Some(end) if end <= len => {}
+            _ => return Err(invalid("file out of the pack")),
+        }
+        index.insert(name, (offset, size));
+    }
+
+    Ok(index)
+}
+
+/// Write the files in a pack, by path with `/` separators.
+pub fn write_pack(
+    files: &[(String, Vec<u8>)],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let index_size: usize =
+        files.iter().map(|(name, _)| 4 + name.len() + 8 + 8).sum();
+    let mut offset = (MAGIC.len() + 4 + 4 + index_size) as u64;
+
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&(files.len() as u32).to_le_bytes())?;
+
+    for (name, content) in files {
+        out.write_all(&(name.len() as u32).to_le_bytes())?;
+        out.write_all(name.as_bytes())?;
+        out.write_all(&offset.to_le_bytes())?;
+        out.write_all(&(content.len() as u64).to_le_bytes())?;
+        offset += content.len() as u64;
+    }
+
+    for (_, content) in files {
+        out.write_all(content)?;
+    }
+
+    Ok(())
+}
+
+/// Bundle every file of a directory tree in a pack, returns the number of
+/// files. Used by `engine pack`.
+pub fn pack_dir(root: &Path, out: &Path) -> io::Result<usize> {
+    let mut files = vec![];
+    collect_files(root, root, &mut files)?;
+    // The same tree always gives the same pack.
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    write_pack(&files, &mut io::BufWriter::new(File::create(out)?))?;
+    Ok(files.len())
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+
+        let name = path
+            .strip_prefix(root)
+            .map_err(|_| invalid("file out of the directory"))?
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((name, fs::read(&path)?));
+    }
+
+    Ok(())
+}
+
+/// Pack of the files, leaked to be mounted like an embedded one.
+#[cfg(test)]
+pub fn test_pack(files: &[(&str, &str)]) -> &'static [u8] {
+    let files: Vec<(String, Vec<u8>)> = files
+        .iter()
+        .map(|(name, content)| (name.to_string(), content.as_bytes().to_vec()))
+        .collect();
+    let mut bytes = vec![];
+    write_pack(&files, &mut bytes).unwrap();
+
+    Box::leak(bytes.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_packed_files() {
+        let bytes = test_pack(&[
+            ("scenes/a.ron", "(a)"),
+            ("textures/b.png", ""),
+            ("c", "c"),
+        ]);
+        let pack = Pack::from_bytes(bytes).unwrap();
+
+        assert_eq!(pack.read("scenes/a.ron").unwrap(), b"(a)");
+        assert_eq!(pack.read("textures/b.png").unwrap(), b"");
+        assert_eq!(pack.read("c").unwrap(), b"c");
+        assert!(pack.contains("c"));
+        assert_eq!(pack.read("d").unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let mut names: Vec<&str> = pack.names().collect();
+        names.sort();
+        assert_eq!(names, vec!["c", "scenes/a.ron", "textures/b.png"]);
+    }
+
+    #[test]
+    fn should_not_read_other_files() {
+        let err = Pack::from_bytes(b"PK\x03\x04 not a pack").err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut truncated = test_pack(&[("a", "abc")]).to_vec();
+        truncated.truncate(10);
+        let truncated = Box::leak(truncated.into_boxed_slice());
+        assert!(Pack::from_bytes(truncated).is_err());
+    }
+
+    #[test]
+    fn should_not_read_corrupt_index() {
+        let entry = |name_len: u32, offset: u64, size: u64| {
+            let mut bytes = MAGIC.to_vec();
+            bytes.extend(&VERSION.to_le_bytes());
+            bytes.extend(&1u32.to_le_bytes());
+            bytes.extend(&name_len.to_le_bytes());
+            bytes.extend(b"a");
+            bytes.extend(&offset.to_le_bytes());
+            bytes.extend(&size.to_le_bytes());
+            Pack::from_bytes(Box::leak(bytes.into_boxed_slice()))
+        };
+
+        assert!(entry(1, 0, 1).is_ok());
+        let huge_name = entry(u32::MAX, 0, 1).err().unwrap();
+        assert_eq!(huge_name.kind(), io::ErrorKind::InvalidData);
+        assert!(entry(1, u64::MAX, 1).is_err());
+        assert!(entry(1, 0, 1000).is_err());
+
+        let mut many = MAGIC.to_vec();
+        many.extend(&VERSION.to_le_bytes());
+        many.extend(&u32::MAX.to_le_bytes());
+        let many = Pack::from_bytes(Box::leak(many.into_boxed_slice()));
+        assert_eq!(many.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::constants::{GAME_TITLE, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::ecs::Events;
+use crate::events::InputEvent;
 use crate::opengl::OpenGL;
 use glutin::{
     dpi, ContextBuilder, DeviceEvent, ElementState, Event, EventsLoop,
@@ -61,7 +63,8 @@ impl Window {
         self.context.window().hide_cursor(is_hide);
     }
 
-    pub fn capture(&mut self) {
+    /// Poll the window events, input is also published to `events`.
+    pub fn capture(&mut self, events: &mut Events<InputEvent>) {
         let mut should_close = false;
         let key_events = &mut self.key_events;
         let mouse_events = &mut self.mouse_events;
@@ -72,13 +75,16 @@ impl Window {
                 Event::WindowEvent { event, .. } => match event {
                     WindowEvent::CloseRequested => {
                         should_close = true;
+                        events.send(InputEvent::CloseRequested);
                     }
                     WindowEvent::KeyboardInput { input, .. } => {
                         if let Some(keycode) = input.virtual_keycode {
                             if input.state == ElementState::Pressed {
                                 key_events.add_keycode(keycode);
+                                events.send(InputEvent::KeyPressed(keycode));
                             } else {
                                 key_events.remove_keycode(keycode);
+                                events.send(InputEvent::KeyReleased(keycode));
                             }
 
                             key_events.set_modifiers(input.modifiers);
@@ -89,6 +95,12 @@ impl Window {
                         mouse_events.button = Some(*button);
                         mouse_events.is_pressed =
                             *state == ElementState::Pressed;
+
+                        events.send(if mouse_events.is_pressed {
+                            InputEvent::MousePressed(*button)
+                        } else {
+                            InputEvent::MouseReleased(*button)
+                        });
                     }
                     _ => (),
                 },
@@ -96,6 +108,7 @@ impl Window {
                     if let DeviceEvent::MouseMotion { delta } = event {
                         mouse_events.delta = *delta;
                         mouse_events.has_moved = true;
+                        events.send(InputEvent::MouseMoved(delta.0, delta.1));
                     }
                 }
                 _ => (),